@@ -0,0 +1,198 @@
+// ASCII-art canvas for drawing shapes onto a grid of characters
+// Each character cell is sampled at its center, so any Shape can be drawn through Shape::contains
+// Output only depends on the shapes drawn, which keeps it stable for comparing against expected text
+
+use crate::geometry::{BoundingBox, Shape};
+
+pub struct Canvas {
+    area: BoundingBox,
+    columns: usize,
+    rows: usize,
+    // World units covered by one column, a row covers twice as much since characters are about twice as tall as wide
+    scale: f64,
+    cells: Vec<char>,
+}
+
+impl Canvas {
+    // Canvas showing 'area' using at most max_columns x max_rows characters
+    // One column covers at least one world unit, larger areas are scaled down to fit
+    pub fn new(area: BoundingBox, max_columns: usize, max_rows: usize) -> Canvas {
+        let (max_columns, max_rows) = (max_columns.max(1) as f64, max_rows.max(1) as f64);
+        let scale = 1f64.max(area.width() / max_columns).max(area.height() / (2.0 * max_rows));
+
+        let columns = ((area.width() / scale).ceil() as usize).max(1);
+        let rows = ((area.height() / (2.0 * scale)).ceil() as usize).max(1);
+        Canvas { area, columns, rows, scale, cells: vec![' '; columns * rows] }
+    }
+
+    // World coordinates of the center of a cell
+    fn cell_center(&self, row: usize, column: usize) -> (f64, f64) {
+        (
+            self.area.min_x + (column as f64 + 0.5) * self.scale,
+            self.area.min_y + (row as f64 + 0.5) * 2.0 * self.scale,
+        )
+    }
+
+    // Cells covered by the shape, a shape smaller than a cell still covers the cell at its center
+    fn coverage(&self, shape: &dyn Shape) -> Vec<bool> {
+        let mut covered = vec![false; self.cells.len()];
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let (x, y) = self.cell_center(row, column);
+                covered[row * self.columns + column] = shape.contains(x, y);
+            }
+        }
+
+        if !covered.contains(&true) {
+            let bbox = shape.bounding_box();
+            let x = (bbox.min_x + bbox.max_x) / 2.0 - self.area.min_x;
+            let y = (bbox.min_y + bbox.max_y) / 2.0 - self.area.min_y;
+            let column = ((x / self.scale) as usize).min(self.columns - 1);
+            let row = ((y / (2.0 * self.scale)) as usize).min(self.rows - 1);
+            covered[row * self.columns + column] = true;
+        }
+        covered
+    }
+
+    // Draws the outline of the shape using '-', '|' and '+' and writes the label on its first inner row
+    // The inside is filled with 'fill', or left as it is when fill is None so that shapes underneath stay visible
+    pub fn draw(&mut self, shape: &dyn Shape, label: &str, fill: Option<char>) {
+        let covered = self.coverage(shape);
+        let (rows, columns) = (self.rows, self.columns);
+        let is_covered = |row: isize, column: isize| {
+            row >= 0
+                && column >= 0
+                && (row as usize) < rows
+                && (column as usize) < columns
+                && covered[row as usize * columns + column as usize]
+        };
+
+        // Inner cells are the covered cells which aren't on the outline
+        let mut inner = vec![false; self.cells.len()];
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let (r, c) = (row as isize, column as isize);
+                if !is_covered(r, c) {
+                    continue;
+                }
+                let top_or_bottom = !is_covered(r - 1, c) || !is_covered(r + 1, c);
+                let left_or_right = !is_covered(r, c - 1) || !is_covered(r, c + 1);
+                let index = row * self.columns + column;
+                match (top_or_bottom, left_or_right) {
+                    (true, true) => self.cells[index] = '+',
+                    (true, false) => self.cells[index] = '-',
+                    (false, true) => self.cells[index] = '|',
+                    (false, false) => {
+                        inner[index] = true;
+                        if let Some(fill) = fill {
+                            self.cells[index] = fill;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Label goes into the first run of inner cells and is cut off where the run ends
+        if let Some(start) = inner.iter().position(|&is_inner| is_inner) {
+            let run = inner[start..].iter().take_while(|&&is_inner| is_inner).count();
+            let run = run.min(self.columns - start % self.columns);
+            for (offset, c) in label.chars().take(run).enumerate() {
+                self.cells[start + offset] = c;
+            }
+        }
+    }
+
+    // Rows of the canvas without trailing spaces, each ending with a line break
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for row in self.cells.chunks(self.columns) {
+            let line: String = row.iter().collect();
+            out += line.trim_end();
+            out.push('\n');
+        }
+        out
+    }
+}
+
+// Draws labelled shapes onto a canvas fitting all of them, largest first so that nested shapes stay visible
+pub fn render_shapes(shapes: &[(&str, &dyn Shape)], max_columns: usize, max_rows: usize) -> String {
+    let area = match shapes.iter().map(|(_, shape)| shape.bounding_box()).reduce(|a, b| a.union(&b)) {
+        Some(area) => area,
+        None => return String::new(),
+    };
+
+    let mut ordered: Vec<&(&str, &dyn Shape)> = shapes.iter().collect();
+    ordered.sort_by(|(_, a), (_, b)| b.area().total_cmp(&a.area()));
+
+    let mut canvas = Canvas::new(area, max_columns, max_rows);
+    for (label, shape) in ordered {
+        canvas.draw(*shape, label, None);
+    }
+    canvas.render()
+}
+
+pub fn run() {
+    use crate::geometry::{Circle, Triangle};
+    use crate::rectangles::PositionedRectangle;
+
+    let outer = PositionedRectangle::new(0, 0, 40, 24).unwrap();
+    let inner = PositionedRectangle::new(4, 4, 14, 10).unwrap();
+    let circle = Circle { center: (30.0, 14.0), radius: 7.0 };
+    let triangle = Triangle { a: (44.0, 0.0), b: (60.0, 24.0), c: (44.0, 24.0) };
+
+    // Nested shapes stay visible inside the outer one, each with its label on its first inner row
+    // Labels are cut off where the inside of the shape ends, like the triangle's
+    let shapes: Vec<(&str, &dyn Shape)> =
+        vec![("outer", &outer), ("inner", &inner), ("circle", &circle), ("triangle", &triangle)];
+    let drawing = render_shapes(&shapes, 80, 40);
+    print!("{}", drawing);
+    let expected = [
+        "+--------------------------------------+    +",
+        "|outer                                 |    |+",
+        "|   +------------+                     |    |t+",
+        "|   |inner       |                     |    |  -+",
+        "|   |            |       +--------+    |    |    +",
+        "|   |            |      +circle    +   |    |     +",
+        "|   +------------+     +            +  |    |      -+",
+        "|                      +            +  |    |        +",
+        "|                       +          +   |    |         +",
+        "|                        +--------+    |    |          -+",
+        "|                                      |    |            +",
+        "+--------------------------------------+    +-------------+",
+    ];
+    assert_eq!(drawing.lines().collect::<Vec<&str>>(), expected);
+
+    // Huge rectangles are scaled down to fit the terminal
+    let huge = PositionedRectangle::new(0, 0, 1_000_000, 500_000).unwrap();
+    let small = PositionedRectangle::new(100_000, 100_000, 300_000, 200_000).unwrap();
+    let drawing = render_shapes(&[("huge", &huge), ("small", &small)], 40, 20);
+    print!("{}", drawing);
+    let expected = [
+        "+--------------------------------------+",
+        "|huge                                  |",
+        "|   +----------+                       |",
+        "|   |small     |                       |",
+        "|   |          |                       |",
+        "|   +----------+                       |",
+        "|                                      |",
+        "|                                      |",
+        "|                                      |",
+        "+--------------------------------------+",
+    ];
+    assert_eq!(drawing.lines().collect::<Vec<&str>>(), expected);
+
+    // Overlapping shapes aren't filled, so the outline of the one drawn first shows through the other
+    let a = PositionedRectangle::new(0, 0, 12, 8).unwrap();
+    let b = PositionedRectangle::new(6, 4, 12, 8).unwrap();
+    let drawing = render_shapes(&[("a", &a), ("b", &b)], 80, 40);
+    print!("{}", drawing);
+    let expected = [
+        "+----------+",
+        "|a         |",
+        "|     +----------+",
+        "+-----|b---+     |",
+        "      |          |",
+        "      +----------+",
+    ];
+    assert_eq!(drawing.lines().collect::<Vec<&str>>(), expected);
+}
@@ -0,0 +1,344 @@
+// CIDR blocks like 10.0.0.0/8 or 2001:db8::/32 built on enums::IpAddr
+// Addresses are turned into plain integers for the arithmetic, IPv4 uses the low 32 bits of a u128
+
+use crate::enums::{AddrParseError, IpAddr};
+use std::fmt;
+use std::str::FromStr;
+
+// subnets() hands out at most 2^16 blocks, splitting ::/0 into /128s would never finish
+const MAX_SPLIT_BITS: u8 = 16;
+
+fn width(addr: &IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(..) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+fn to_bits(addr: &IpAddr) -> u128 {
+    match *addr {
+        IpAddr::V4(a, b, c, d) => u32::from_be_bytes([a, b, c, d]) as u128,
+        IpAddr::V6(groups) => groups.iter().fold(0, |bits, &group| (bits << 16) | group as u128),
+    }
+}
+
+fn from_bits(bits: u128, width: u8) -> IpAddr {
+    if width == 32 {
+        let [a, b, c, d] = (bits as u32).to_be_bytes();
+        IpAddr::V4(a, b, c, d)
+    } else {
+        let mut groups = [0u16; 8];
+        for (i, group) in groups.iter_mut().enumerate() {
+            *group = (bits >> (112 - 16 * i)) as u16;
+        }
+        IpAddr::V6(groups)
+    }
+}
+
+// Mask with the top 'prefix' bits of a 'width' bit address set
+fn mask(prefix: u8, width: u8) -> u128 {
+    let all = if width == 128 { u128::MAX } else { (1 << width) - 1 };
+    // Shifting a u128 by 128 overflows, so a full mask is handled on its own
+    if prefix == width {
+        all
+    } else {
+        all & !(all >> prefix)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CidrError {
+    // No "/" in the input
+    MissingPrefix(String),
+    InvalidAddress(AddrParseError),
+    InvalidPrefix(String),
+    // Prefix longer than the 32 or 128 bits of the address
+    PrefixTooLong { prefix: u8, max: u8 },
+    // subnets() was asked for a prefix shorter than the block's own
+    PrefixTooShort { prefix: u8, min: u8 },
+    TooManySubnets(u8),
+}
+
+impl fmt::Display for CidrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CidrError::MissingPrefix(s) => write!(f, "{:?} has no /prefix", s),
+            CidrError::InvalidAddress(e) => write!(f, "invalid address: {}", e),
+            CidrError::InvalidPrefix(s) => write!(f, "{:?} is not a prefix length", s),
+            CidrError::PrefixTooLong { prefix, max } => write!(f, "prefix /{} is longer than {} bits", prefix, max),
+            CidrError::PrefixTooShort { prefix, min } => write!(f, "prefix /{} is shorter than /{}", prefix, min),
+            CidrError::TooManySubnets(prefix) => {
+                write!(f, "splitting into /{} gives more than {} subnets", prefix, 1 << MAX_SPLIT_BITS)
+            }
+        }
+    }
+}
+
+impl From<AddrParseError> for CidrError {
+    fn from(e: AddrParseError) -> CidrError {
+        CidrError::InvalidAddress(e)
+    }
+}
+
+// Block of addresses sharing their first 'prefix' bits
+// Only the network address is kept, so 10.1.2.3/8 and 10.0.0.0/8 are the same block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    // Host bits of 'addr' are cleared
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Cidr, CidrError> {
+        let max = width(&addr);
+        if prefix > max {
+            return Err(CidrError::PrefixTooLong { prefix, max });
+        }
+        Ok(Cidr { network: from_bits(to_bits(&addr) & mask(prefix, max), max), prefix })
+    }
+
+    pub fn network(&self) -> IpAddr {
+        self.network
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    pub fn is_ipv6(&self) -> bool {
+        width(&self.network) == 128
+    }
+
+    pub fn netmask(&self) -> IpAddr {
+        let width = width(&self.network);
+        from_bits(mask(self.prefix, width), width)
+    }
+
+    // Last address of the block
+    pub fn last(&self) -> IpAddr {
+        let width = width(&self.network);
+        let host_bits = mask(width, width) & !mask(self.prefix, width);
+        from_bits(to_bits(&self.network) | host_bits, width)
+    }
+
+    // IPv6 has no broadcast, and neither do IPv4 /31 and /32 blocks (RFC 3021)
+    pub fn broadcast(&self) -> Option<IpAddr> {
+        if self.is_ipv6() || self.prefix >= 31 {
+            None
+        } else {
+            Some(self.last())
+        }
+    }
+
+    // First and last address which can be given to a host
+    // IPv4 leaves out the network and broadcast addresses where there are any, IPv6 uses every address
+    pub fn hosts(&self) -> (IpAddr, IpAddr) {
+        let width = width(&self.network);
+        match self.broadcast() {
+            Some(broadcast) => {
+                (from_bits(to_bits(&self.network) + 1, width), from_bits(to_bits(&broadcast) - 1, width))
+            }
+            None => (self.network, self.last()),
+        }
+    }
+
+    // Number of addresses in the block, ::/0 holds 2^128 addresses which is saturated to u128::MAX
+    pub fn size(&self) -> u128 {
+        let host_bits = width(&self.network) - self.prefix;
+        if host_bits == 128 {
+            u128::MAX
+        } else {
+            1 << host_bits
+        }
+    }
+
+    // Addresses of the other IP version are never contained
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        let bits = width(&self.network);
+        bits == width(addr) && to_bits(addr) & mask(self.prefix, bits) == to_bits(&self.network)
+    }
+
+    pub fn contains_cidr(&self, other: &Cidr) -> bool {
+        other.prefix >= self.prefix && self.contains(&other.network)
+    }
+
+    // The block split into all blocks with the longer prefix 'prefix', in order
+    pub fn subnets(&self, prefix: u8) -> Result<Vec<Cidr>, CidrError> {
+        let width = width(&self.network);
+        if prefix > width {
+            return Err(CidrError::PrefixTooLong { prefix, max: width });
+        }
+        if prefix < self.prefix {
+            return Err(CidrError::PrefixTooShort { prefix, min: self.prefix });
+        }
+        let extra_bits = prefix - self.prefix;
+        if extra_bits > MAX_SPLIT_BITS {
+            return Err(CidrError::TooManySubnets(prefix));
+        }
+
+        let count = 1u128 << extra_bits;
+        let step = if prefix == 0 { 0 } else { 1u128 << (width - prefix) };
+        let start = to_bits(&self.network);
+        Ok((0..count).map(|i| Cidr { network: from_bits(start + i * step, width), prefix }).collect())
+    }
+
+    // The block one bit shorter containing this one, None for /0
+    pub fn supernet(&self) -> Option<Cidr> {
+        let prefix = self.prefix.checked_sub(1)?;
+        Some(Cidr::new(self.network, prefix).unwrap())
+    }
+}
+
+// Smallest list of blocks covering exactly the same addresses as 'blocks'
+// Blocks inside other blocks are dropped and neighbouring halves are merged into their supernet, IPv4 comes first
+pub fn aggregate(blocks: &[Cidr]) -> Vec<Cidr> {
+    let mut sorted = blocks.to_vec();
+    sorted.sort_by_key(|block| (block.is_ipv6(), to_bits(&block.network), block.prefix));
+
+    let mut result: Vec<Cidr> = Vec::new();
+    for block in sorted {
+        // Sorting puts a containing block right before the blocks inside it
+        if result.last().is_some_and(|last| last.contains_cidr(&block)) {
+            continue;
+        }
+        result.push(block);
+
+        // Two blocks with the same supernet which together fill it are replaced by the supernet,
+        // which may in turn complete a pair with the block before it
+        while result.len() >= 2 {
+            let (a, b) = (result[result.len() - 2], result[result.len() - 1]);
+            match (a.supernet(), b.supernet()) {
+                (Some(parent), Some(other)) if a.prefix == b.prefix && parent == other => {
+                    result.truncate(result.len() - 2);
+                    result.push(parent);
+                }
+                _ => break,
+            }
+        }
+    }
+    result
+}
+
+impl FromStr for Cidr {
+    type Err = CidrError;
+
+    fn from_str(s: &str) -> Result<Cidr, CidrError> {
+        let s = s.trim();
+        let (addr, prefix) = match s.find('/') {
+            Some(i) => (&s[..i], &s[i + 1..]),
+            None => return Err(CidrError::MissingPrefix(s.to_string())),
+        };
+        let addr: IpAddr = addr.parse()?;
+        // u8 would also accept "+8"
+        if !prefix.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(CidrError::InvalidPrefix(prefix.to_string()));
+        }
+        let prefix: u8 = prefix.parse().map_err(|_| CidrError::InvalidPrefix(prefix.to_string()))?;
+        Cidr::new(addr, prefix)
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+fn parse_blocks(args: &[String]) -> Result<Vec<Cidr>, String> {
+    args.iter().map(|arg| arg.parse().map_err(|e| format!("{}: {}", arg, e))).collect()
+}
+
+fn print_block(block: &Cidr) {
+    let (first, last) = block.hosts();
+    println!("Block:     {}", block);
+    println!("Netmask:   {}", block.netmask());
+    println!("Network:   {}", block.network());
+    match block.broadcast() {
+        Some(broadcast) => println!("Broadcast: {}", broadcast),
+        None => println!("Broadcast: none"),
+    }
+    println!("Hosts:     {} - {}", first, last);
+    println!("Addresses: {}", block.size());
+}
+
+// 'cidr' command:
+//   cidr <block> [address ...]       details of the block and whether it contains each address
+//   cidr split <block> <prefix>      the block divided into blocks of a longer prefix
+//   cidr aggregate <block> ...       the shortest list of blocks covering the given ones
+pub fn calculator(args: &[String]) -> Result<(), String> {
+    let usage = "usage: cidr <block> [address ...] | cidr split <block> <prefix> | cidr aggregate <block> ...";
+    match args.split_first() {
+        Some((action, rest)) if action == "split" => {
+            let (block, prefix) = match rest {
+                [block, prefix] => (block, prefix),
+                _ => return Err(String::from(usage)),
+            };
+            let block: Cidr = block.parse().map_err(|e| format!("{}: {}", block, e))?;
+            let prefix: u8 = prefix
+                .trim_start_matches('/')
+                .parse()
+                .map_err(|_| format!("{:?} is not a prefix length", prefix))?;
+            for subnet in block.subnets(prefix).map_err(|e| e.to_string())? {
+                println!("{}", subnet);
+            }
+            Ok(())
+        }
+        Some((action, rest)) if action == "aggregate" => {
+            for block in aggregate(&parse_blocks(rest)?) {
+                println!("{}", block);
+            }
+            Ok(())
+        }
+        Some((block, addresses)) => {
+            let block: Cidr = block.parse().map_err(|e| format!("{}: {}", block, e))?;
+            print_block(&block);
+            for address in addresses {
+                let addr: IpAddr = address.parse().map_err(|e| format!("{}: {}", address, e))?;
+                let verdict = if block.contains(&addr) { "inside" } else { "outside" };
+                println!("{} is {} {}", addr, verdict, block);
+            }
+            Ok(())
+        }
+        None => Err(String::from(usage)),
+    }
+}
+
+pub fn run() {
+    let block: Cidr = "192.168.1.77/24".parse().unwrap();
+    print_block(&block);
+    assert_eq!(block.to_string(), "192.168.1.0/24");
+    assert_eq!(block.netmask().to_string(), "255.255.255.0");
+    assert!(block.contains(&"192.168.1.200".parse().unwrap()));
+    assert!(!block.contains(&"192.168.2.1".parse().unwrap()));
+    assert!(!block.contains(&"::ffff:192.168.1.1".parse().unwrap()));
+
+    let v6: Cidr = "2001:db8::/32".parse().unwrap();
+    print_block(&v6);
+    assert_eq!(v6.last().to_string(), "2001:db8:ffff:ffff:ffff:ffff:ffff:ffff");
+    assert_eq!(v6.netmask().to_string(), "ffff:ffff::");
+
+    // Point-to-point links and single hosts use every address
+    let link: Cidr = "10.0.0.0/31".parse().unwrap();
+    assert_eq!(link.broadcast(), None);
+    assert_eq!(link.hosts(), ("10.0.0.0".parse().unwrap(), "10.0.0.1".parse().unwrap()));
+
+    let subnets: Vec<String> = block.subnets(26).unwrap().iter().map(|s| s.to_string()).collect();
+    println!("{} in /26: {:?}", block, subnets);
+    assert_eq!(subnets, ["192.168.1.0/26", "192.168.1.64/26", "192.168.1.128/26", "192.168.1.192/26"]);
+    let supernet = block.supernet().unwrap();
+    assert_eq!((supernet.network().to_string(), supernet.prefix(), supernet.size()), (String::from("192.168.0.0"), 23, 512));
+    assert!(supernet.contains_cidr(&block) && !block.contains_cidr(&supernet));
+    assert_eq!("0.0.0.0/0".parse::<Cidr>().unwrap().supernet(), None);
+
+    let blocks: Vec<Cidr> = ["10.0.1.0/24", "10.0.0.0/24", "10.0.2.0/23", "10.0.0.128/25", "2001:db8::/33", "2001:db8:8000::/33"]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    let merged: Vec<String> = aggregate(&blocks).iter().map(|b| b.to_string()).collect();
+    assert_eq!(merged, ["10.0.0.0/22", "2001:db8::/32"]);
+    println!("Aggregated: {:?}", merged);
+
+    println!("{}", "10.0.0.0/33".parse::<Cidr>().unwrap_err());
+    println!("{}", "10.0.0.0".parse::<Cidr>().unwrap_err());
+}
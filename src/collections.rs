@@ -12,9 +12,20 @@ pub fn run(){
     hashmaps::run();
 }
 
-mod vectors{
+pub mod vectors{
     // Bringing 'type_of' function into current scope from parent scope
     use super::type_of;
+    use crate::stats;
+
+    // To create a vector of different parameter types and known at compile time
+    // spreadsheet.rs builds a whole spreadsheet out of these cells
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum SpreadsheetCell {
+        Int(i32),
+        Float(f64),
+        Text(String),
+    }
+
     pub fn run(){
         println!("\nVector run....\n");
 
@@ -64,13 +75,7 @@ mod vectors{
             println!("i1: {} i2: {}", i1, i2);
         }
 
-        // To create a vector of different parameter types and known at compile time
-        enum SpreadsheetCell {
-            Int(i32),
-            Float(f64),
-            Text(String),
-        }
-
+        // SpreadsheetCell is defined above so that it can be used outside this function too
         let row = vec![
             SpreadsheetCell::Int(2),
             SpreadsheetCell::Text(String::from("Boo")),
@@ -105,6 +110,9 @@ mod vectors{
 
         println!("v1: {:?}", v1);
 
+        // Statistics over the Vec<i32>, see stats.rs for the functions
+        println!("mean: {:?}, median: {:?}, modes: {:?}", stats::mean(&v1), stats::median(&v1), stats::modes(&v1));
+
     }
 }
 
@@ -150,10 +158,13 @@ mod strings{
         for c in "नमस्ते".chars() {
             println!("{}", c);
         }
-        // Strings can be iterated over Raw Bytes
-        for b in "नमस्ते".chars() {
-            println!("{}", b);
+        // Strings can be iterated over Raw Bytes, 3 UTF-8 bytes for every one of the Devanagari characters above
+        for b in "नमस्ते".bytes() {
+            println!("{:#04x}", b);
         }
+        // What is seen as one letter (a grapheme) can be several characters, e.g. "स्ते" is 4 of them
+        // inspect.rs shows the bytes, characters and graphemes of any string side by side
+        println!("Graphemes: {:?}", crate::inspect::graphemes("नमस्ते"));
         
 
     }
@@ -0,0 +1,281 @@
+// RGB colors with parsing, HSL/HSV conversions, blending and WCAG contrast
+// Channels are u8 and hence can never be negative or larger than 255
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)] // Deriving Debug trait for printing Color tuple struct
+pub struct Color(pub u8, pub u8, pub u8);
+
+// Hue is in degrees [0, 360), saturation and lightness are in [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub h: f64,
+    pub s: f64,
+    pub l: f64,
+}
+
+// Hue is in degrees [0, 360), saturation and value are in [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    pub h: f64,
+    pub s: f64,
+    pub v: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ColorError {
+    // Input is neither "#..." nor "rgb(...)"
+    UnknownFormat(String),
+    // Hex colors need 3 or 6 digits after '#'
+    HexLength(usize),
+    InvalidHexDigit(char),
+    // rgb() needs exactly 3 channels
+    ChannelCount(usize),
+    InvalidChannel(String),
+    ChannelOutOfRange(String),
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorError::UnknownFormat(s) => write!(f, "{:?} is not #rrggbb, #rgb or rgb(r, g, b)", s),
+            ColorError::HexLength(len) => write!(f, "expected 3 or 6 hex digits but found {}", len),
+            ColorError::InvalidHexDigit(c) => write!(f, "{:?} is not a hex digit", c),
+            ColorError::ChannelCount(count) => write!(f, "expected 3 channels but found {}", count),
+            ColorError::InvalidChannel(s) => write!(f, "{:?} is not a number", s),
+            ColorError::ChannelOutOfRange(s) => write!(f, "channel {} is not between 0 and 255", s),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Color, ColorError> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix('#') {
+            parse_hex(hex)
+        } else if let Some(args) = s.strip_prefix("rgb(").and_then(|rest| rest.strip_suffix(')')) {
+            parse_rgb_args(args)
+        } else {
+            Err(ColorError::UnknownFormat(s.to_string()))
+        }
+    }
+}
+
+fn parse_hex(hex: &str) -> Result<Color, ColorError> {
+    if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(ColorError::InvalidHexDigit(c));
+    }
+    // All characters are ASCII hex digits now, so indexing bytes is safe
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).unwrap();
+    match hex.len() {
+        // "#rgb" is short for "#rrggbb"
+        3 => Ok(Color(digit(0) * 17, digit(1) * 17, digit(2) * 17)),
+        6 => Ok(Color(
+            digit(0) * 16 + digit(1),
+            digit(2) * 16 + digit(3),
+            digit(4) * 16 + digit(5),
+        )),
+        len => Err(ColorError::HexLength(len)),
+    }
+}
+
+fn parse_rgb_args(args: &str) -> Result<Color, ColorError> {
+    let parts: Vec<&str> = args.split(',').map(|part| part.trim()).collect();
+    if parts.len() != 3 {
+        return Err(ColorError::ChannelCount(parts.len()));
+    }
+
+    let mut channels = [0u8; 3];
+    for (channel, part) in channels.iter_mut().zip(parts.iter()) {
+        // Parsing as i64 first tells apart "300" (out of range) from "abc" (not a number)
+        let value: i64 = part.parse().map_err(|_| ColorError::InvalidChannel(part.to_string()))?;
+        if !(0..=255).contains(&value) {
+            return Err(ColorError::ChannelOutOfRange(part.to_string()));
+        }
+        *channel = value as u8;
+    }
+    Ok(Color(channels[0], channels[1], channels[2]))
+}
+
+// Displayed as lowercase "#rrggbb"
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+// Converts a channel in [0, 1] back to u8
+fn to_channel(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+// Hue in degrees along with the largest and smallest channels in [0, 1], shared by HSL and HSV
+fn hue_max_min(color: Color) -> (f64, f64, f64) {
+    let (r, g, b) = (color.0 as f64 / 255.0, color.1 as f64 / 255.0, color.2 as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * (((g - b) / delta).rem_euclid(6.0))
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, max, min)
+}
+
+// Builds a color from a hue and the chroma/offset as described for both HSL and HSV
+fn from_hue(h: f64, chroma: f64, offset: f64) -> Color {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    Color(to_channel(r + offset), to_channel(g + offset), to_channel(b + offset))
+}
+
+// Converts an sRGB channel to linear light as defined by WCAG 2
+fn linearize(channel: u8) -> f64 {
+    let c = channel as f64 / 255.0;
+    if c <= 0.03928 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+impl Color {
+    pub fn to_hsl(self) -> Hsl {
+        let (h, max, min) = hue_max_min(self);
+        let l = (max + min) / 2.0;
+        let s = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * l - 1.0).abs()) };
+        Hsl { h, s, l }
+    }
+
+    pub fn from_hsl(hsl: Hsl) -> Color {
+        let (s, l) = (hsl.s.clamp(0.0, 1.0), hsl.l.clamp(0.0, 1.0));
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        from_hue(hsl.h, chroma, l - chroma / 2.0)
+    }
+
+    pub fn to_hsv(self) -> Hsv {
+        let (h, max, min) = hue_max_min(self);
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+        Hsv { h, s, v: max }
+    }
+
+    pub fn from_hsv(hsv: Hsv) -> Color {
+        let (s, v) = (hsv.s.clamp(0.0, 1.0), hsv.v.clamp(0.0, 1.0));
+        let chroma = v * s;
+        from_hue(hsv.h, chroma, v - chroma)
+    }
+
+    // Draws 'top' over this color with the given opacity (0 keeps self, 1 gives top)
+    pub fn blend(self, top: Color, alpha: f64) -> Color {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let mix = |bottom: u8, top: u8| (bottom as f64 * (1.0 - alpha) + top as f64 * alpha).round() as u8;
+        Color(mix(self.0, top.0), mix(self.1, top.1), mix(self.2, top.2))
+    }
+
+    // Relative luminance as defined by WCAG 2, 0 for black and 1 for white
+    pub fn luminance(self) -> f64 {
+        0.2126 * linearize(self.0) + 0.7152 * linearize(self.1) + 0.0722 * linearize(self.2)
+    }
+
+    // WCAG contrast ratio between 1:1 and 21:1, the order of the colors doesn't matter
+    pub fn contrast_ratio(self, other: Color) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+// A swatch with the color as background and the most readable of black or white as text
+fn swatch(color: Color) -> String {
+    let black = Color(0, 0, 0);
+    let white = Color(255, 255, 255);
+    let text = if color.contrast_ratio(black) >= color.contrast_ratio(white) { black } else { white };
+    format!(
+        "\x1b[48;2;{};{};{}m\x1b[38;2;{};{};{}m {} \x1b[0m",
+        color.0, color.1, color.2, text.0, text.1, text.2, color
+    )
+}
+
+fn print_color(color: Color) {
+    let hsl = color.to_hsl();
+    let hsv = color.to_hsv();
+    println!(
+        "{}  hsl({:5.1}, {:3.0}%, {:3.0}%)  hsv({:5.1}, {:3.0}%, {:3.0}%)  luminance {:.3}  contrast on white {:5.2}:1, on black {:5.2}:1",
+        swatch(color),
+        hsl.h,
+        hsl.s * 100.0,
+        hsl.l * 100.0,
+        hsv.h,
+        hsv.s * 100.0,
+        hsv.v * 100.0,
+        color.luminance(),
+        color.contrast_ratio(Color(255, 255, 255)),
+        color.contrast_ratio(Color(0, 0, 0)),
+    );
+}
+
+// 'palette' command: prints swatches for the given colors, or a default palette when there are none
+// When two or more colors are given, a gradient between the first two is printed too
+pub fn palette(args: &[String]) -> Result<(), String> {
+    let defaults = ["#000", "#fff", "#e74c3c", "#2ecc71", "#3498db", "rgb(241, 196, 15)", "#9b59b6"];
+    let inputs: Vec<&str> = if args.is_empty() {
+        defaults.to_vec()
+    } else {
+        args.iter().map(|s| s.as_str()).collect()
+    };
+
+    let mut colors = Vec::new();
+    for input in inputs {
+        let color: Color = input.parse().map_err(|e: ColorError| format!("{}: {}", input, e))?;
+        colors.push(color);
+    }
+
+    for &color in &colors {
+        print_color(color);
+    }
+
+    if colors.len() >= 2 {
+        println!();
+        let steps = 8;
+        let gradient: Vec<String> = (0..=steps)
+            .map(|i| swatch(colors[0].blend(colors[1], i as f64 / steps as f64)))
+            .collect();
+        println!("{}", gradient.join(""));
+    }
+    Ok(())
+}
+
+pub fn run() {
+    let red: Color = "#ff0000".parse().unwrap();
+    let teal: Color = "#0aa".parse().unwrap();
+    let grey: Color = "rgb(128, 128, 128)".parse().unwrap();
+    println!("red: {} teal: {} grey: {}", red, teal, grey);
+
+    // Invalid input gives a descriptive error
+    println!("{:?}", "rgb(300, 0, 0)".parse::<Color>());
+    println!("{:?}", "#12345".parse::<Color>());
+
+    // Conversions to HSL and HSV and back give the same color
+    println!("teal: {:?} {:?}", teal.to_hsl(), teal.to_hsv());
+    assert_eq!(Color::from_hsl(teal.to_hsl()), teal);
+    assert_eq!(Color::from_hsv(teal.to_hsv()), teal);
+
+    println!("red blended 50% over grey: {}", grey.blend(red, 0.5));
+    println!("Contrast of grey on white: {:.2}:1", grey.contrast_ratio(Color(255, 255, 255)));
+}
@@ -0,0 +1,173 @@
+// Line-based console turning commands into enums::Message and applying them to a Screen
+//
+//   move <x> <y>        moves the cursor by (x, y)
+//   write <text>        appends the rest of the line, spaces included
+//   color <r> <g> <b>   sets the colour, channels between 0 and 255
+//   quit                stops the console
+//
+// Empty lines and lines starting with '#' are skipped, which makes it easy to write scripts
+
+use crate::enums::Message;
+use crate::screen::Screen;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+#[derive(Debug, PartialEq)]
+pub enum CommandError {
+    UnknownCommand(String),
+    // Argument 'name' of the command is missing
+    MissingArgument { command: &'static str, name: &'static str },
+    InvalidArgument { name: &'static str, value: String },
+    UnexpectedArgument { command: &'static str, value: String },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::UnknownCommand(command) => {
+                write!(f, "unknown command {:?}, expected move, write, color or quit", command)
+            }
+            CommandError::MissingArgument { command, name } => write!(f, "{} is missing <{}>", command, name),
+            CommandError::InvalidArgument { name, value } => write!(f, "<{}> {:?} is not a number", name, value),
+            CommandError::UnexpectedArgument { command, value } => {
+                write!(f, "{} does not take the extra argument {:?}", command, value)
+            }
+        }
+    }
+}
+
+// Takes the named integer arguments of a command from 'args' and rejects any left over
+fn integers<'a, I>(command: &'static str, names: &[&'static str], mut args: I) -> Result<Vec<i32>, CommandError>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let mut values = Vec::new();
+    for &name in names {
+        let arg = args.next().ok_or(CommandError::MissingArgument { command, name })?;
+        let value = arg.parse().map_err(|_| CommandError::InvalidArgument { name, value: arg.to_string() })?;
+        values.push(value);
+    }
+    match args.next() {
+        Some(extra) => Err(CommandError::UnexpectedArgument { command, value: extra.to_string() }),
+        None => Ok(values),
+    }
+}
+
+// Message for one line, None for empty and comment lines
+pub fn parse_line(line: &str) -> Result<Option<Message>, CommandError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let (command, rest) = match line.find(char::is_whitespace) {
+        Some(i) => (&line[..i], line[i..].trim_start()),
+        None => (line, ""),
+    };
+    let args = rest.split_whitespace();
+    let message = match command {
+        "move" => {
+            let xy = integers("move", &["x", "y"], args)?;
+            Message::Move { x: xy[0], y: xy[1] }
+        }
+        "write" => Message::Write(rest.to_string()),
+        "color" => {
+            let rgb = integers("color", &["r", "g", "b"], args)?;
+            Message::ChangeColor(rgb[0], rgb[1], rgb[2])
+        }
+        "quit" => {
+            integers("quit", &[], args)?;
+            Message::Quit
+        }
+        _ => return Err(CommandError::UnknownCommand(command.to_string())),
+    };
+    Ok(Some(message))
+}
+
+// Runs every line of 'input' against the screen and prints the screen after each message
+// Interactive sessions show a prompt and carry on after a bad line, scripts stop at the first one
+pub fn interpret<R: BufRead>(input: R, screen: &mut Screen, interactive: bool) -> Result<(), String> {
+    let prompt = || {
+        if interactive {
+            print!("> ");
+            io::stdout().flush().ok();
+        }
+    };
+
+    prompt();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        let applied = parse_line(&line)
+            .map_err(|e| e.to_string())
+            .and_then(|message| match message {
+                Some(message) => screen.apply(&message).map(|_| true).map_err(|e| e.to_string()),
+                None => Ok(false),
+            });
+        match applied {
+            Ok(true) => println!("{}", screen),
+            Ok(false) => (),
+            Err(e) if interactive => println!("error: {}", e),
+            Err(e) => return Err(format!("line {}: {}", i + 1, e)),
+        }
+        if !screen.is_running() {
+            return Ok(());
+        }
+        prompt();
+    }
+    Ok(())
+}
+
+// 'console' command: reads commands from the script file when one is given, otherwise from stdin
+pub fn console(args: &[String]) -> Result<(), String> {
+    let mut screen = Screen::new();
+    match args {
+        [] => {
+            let stdin = io::stdin();
+            let input = stdin.lock();
+            interpret(input, &mut screen, true)
+        }
+        [path] => {
+            let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+            interpret(BufReader::new(file), &mut screen, false).map_err(|e| format!("{}: {}", path, e))
+        }
+        _ => Err(String::from("usage: console [script]")),
+    }
+}
+
+pub fn run() {
+    for line in ["move 3 4", "write hello  world", "color 255 0 0", "quit", "# comment"].iter() {
+        println!("{:20} {:?}", line, parse_line(line));
+    }
+
+    // Errors name the argument at fault
+    let errors = [
+        ("move 3", CommandError::MissingArgument { command: "move", name: "y" }),
+        ("move", CommandError::MissingArgument { command: "move", name: "x" }),
+        ("color 1 2", CommandError::MissingArgument { command: "color", name: "b" }),
+        ("move 3 four", CommandError::InvalidArgument { name: "y", value: String::from("four") }),
+        ("color x 2 3", CommandError::InvalidArgument { name: "r", value: String::from("x") }),
+        ("color 1 2 3 4", CommandError::UnexpectedArgument { command: "color", value: String::from("4") }),
+        ("quit now", CommandError::UnexpectedArgument { command: "quit", value: String::from("now") }),
+        ("jump 1", CommandError::UnknownCommand(String::from("jump"))),
+    ];
+    for (line, expected) in errors.iter() {
+        let error = parse_line(line).unwrap_err();
+        println!("{:20} {}", line, error);
+        assert_eq!(&error, expected, "{:?}", line);
+    }
+    assert_eq!(parse_line("move 3 four").unwrap_err().to_string(), "<y> \"four\" is not a number");
+    assert_eq!(parse_line("color 1 2").unwrap_err().to_string(), "color is missing <b>");
+
+    let script = "write Hi\nmove 1 -1\ncolor 0 0 255\nquit\nwrite ignored\n";
+    let mut screen = Screen::new();
+    interpret(script.as_bytes(), &mut screen, false).unwrap();
+    assert_eq!(screen.text(), "Hi");
+
+    let mut screen = Screen::new();
+    let error = interpret("move 1 1\ncolor 0 0 256\n".as_bytes(), &mut screen, false).unwrap_err();
+    println!("{}", error);
+    assert_eq!(error, "line 2: colour (0, 0, 256) has channels outside 0 to 255");
+    let error = interpret("move 1 1\nmove 2 y\n".as_bytes(), &mut screen, false).unwrap_err();
+    assert_eq!(error, "line 2: <y> \"y\" is not a number");
+}
@@ -0,0 +1,193 @@
+// Reading and writing comma separated values as described in RFC 4180
+// Fields containing commas, quotes or line breaks are wrapped in quotes and quotes inside them are doubled
+// The *_with functions take another delimiter instead of the comma, such as ';' or '\t'
+
+use std::fmt;
+
+pub const DEFAULT_DELIMITER: char = ',';
+
+// Quotes and line breaks already mean something in CSV, so they can't separate fields
+pub fn is_valid_delimiter(delimiter: char) -> bool {
+    !matches!(delimiter, '"' | '\r' | '\n')
+}
+
+// A parsed row along with where it was found in the input
+#[derive(Debug, PartialEq)]
+pub struct Record {
+    // 1-based line on which the record starts
+    pub line: usize,
+    // 1-based column (in characters) at which each field starts
+    pub columns: Vec<usize>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ErrorKind {
+    // Input ended inside a quoted field
+    Unterminated,
+    // A quote appeared in the middle of an unquoted field
+    StrayQuote,
+    // Something other than a delimiter or line break followed a closing quote
+    TextAfterClosingQuote(char),
+    // The delimiter given to parse_with() can't be used, see is_valid_delimiter()
+    InvalidDelimiter(char),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The delimiter is checked before reading anything, so there is no position to show
+        if !matches!(self.kind, ErrorKind::InvalidDelimiter(_)) {
+            write!(f, "line {}, column {}: ", self.line, self.column)?;
+        }
+        match self.kind {
+            ErrorKind::Unterminated => write!(f, "quoted field is never closed"),
+            ErrorKind::StrayQuote => write!(f, "quote inside an unquoted field"),
+            ErrorKind::TextAfterClosingQuote(c) => write!(f, "unexpected {:?} after closing quote", c),
+            ErrorKind::InvalidDelimiter(c) => write!(f, "{:?} can't be used as a delimiter", c),
+        }
+    }
+}
+
+// Quotes the field only when it is needed
+pub fn escape_field_with(field: &str, delimiter: char) -> String {
+    let needs_quotes = field.contains(&[delimiter, '"', '\n', '\r'][..])
+        || field.starts_with(' ')
+        || field.ends_with(' ');
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Joins the escaped fields into one line including the line break
+// A record of one empty field is written as "" since an empty line would be skipped when parsing
+pub fn write_record(fields: &[&str]) -> String {
+    write_record_with(fields, DEFAULT_DELIMITER)
+}
+
+pub fn write_record_with(fields: &[&str], delimiter: char) -> String {
+    if let [""] = fields {
+        return String::from("\"\"\n");
+    }
+    let escaped: Vec<String> = fields.iter().map(|f| escape_field_with(f, delimiter)).collect();
+    escaped.join(&delimiter.to_string()) + "\n"
+}
+
+// States of the parser while it walks over the characters of the input
+enum State {
+    FieldStart,
+    Unquoted,
+    Quoted,
+    // A quote was seen inside a quoted field, it either closes the field or is the first of a doubled quote
+    QuoteInQuoted,
+}
+
+// Parses every record of the input, accepting both "\n" and "\r\n" line breaks
+// Empty lines are skipped, a line holding only "" is a record of one empty field
+pub fn parse(input: &str) -> Result<Vec<Record>, ParseError> {
+    parse_with(input, DEFAULT_DELIMITER)
+}
+
+pub fn parse_with(input: &str, delimiter: char) -> Result<Vec<Record>, ParseError> {
+    if !is_valid_delimiter(delimiter) {
+        return Err(ParseError { line: 1, column: 0, kind: ErrorKind::InvalidDelimiter(delimiter) });
+    }
+    let mut records = Vec::new();
+
+    let mut state = State::FieldStart;
+    let mut field = String::new();
+    let mut record: Option<Record> = None;
+    let mut quote_start = (0, 0);
+    let (mut line, mut column) = (1, 0);
+
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        column += 1;
+        // "\r\n" is treated as a single line break
+        let c = if c == '\r' && chars.peek() == Some(&'\n') {
+            chars.next();
+            '\n'
+        } else {
+            c
+        };
+
+        // A record starts at the first character of a line
+        let current = record.get_or_insert_with(|| Record { line, columns: vec![column], fields: Vec::new() });
+
+        match state {
+            State::FieldStart | State::Unquoted => match c {
+                '"' if matches!(state, State::FieldStart) => {
+                    quote_start = (line, column);
+                    state = State::Quoted;
+                }
+                '"' => return Err(ParseError { line, column, kind: ErrorKind::StrayQuote }),
+                c if c == delimiter => {
+                    current.fields.push(std::mem::take(&mut field));
+                    current.columns.push(column + 1);
+                    state = State::FieldStart;
+                }
+                '\n' => {
+                    let mut finished = record.take().unwrap();
+                    finished.fields.push(std::mem::take(&mut field));
+                    // A line with nothing on it is not a record
+                    if finished.fields.len() > 1 || !finished.fields[0].is_empty() {
+                        records.push(finished);
+                    }
+                    state = State::FieldStart;
+                }
+                _ => {
+                    field.push(c);
+                    state = State::Unquoted;
+                }
+            },
+            State::Quoted => match c {
+                '"' => state = State::QuoteInQuoted,
+                _ => field.push(c),
+            },
+            State::QuoteInQuoted => match c {
+                '"' => {
+                    field.push('"');
+                    state = State::Quoted;
+                }
+                c if c == delimiter => {
+                    current.fields.push(std::mem::take(&mut field));
+                    current.columns.push(column + 1);
+                    state = State::FieldStart;
+                }
+                '\n' => {
+                    let mut finished = record.take().unwrap();
+                    finished.fields.push(std::mem::take(&mut field));
+                    records.push(finished);
+                    state = State::FieldStart;
+                }
+                _ => return Err(ParseError { line, column, kind: ErrorKind::TextAfterClosingQuote(c) }),
+            },
+        }
+
+        if c == '\n' {
+            line += 1;
+            column = 0;
+        }
+    }
+
+    if let State::Quoted = state {
+        let (line, column) = quote_start;
+        return Err(ParseError { line, column, kind: ErrorKind::Unterminated });
+    }
+    // Last record may not end with a line break
+    if let Some(mut finished) = record {
+        finished.fields.push(field);
+        if finished.fields.len() > 1 || !finished.fields[0].is_empty() || matches!(state, State::QuoteInQuoted) {
+            records.push(finished);
+        }
+    }
+    Ok(records)
+}
@@ -0,0 +1,266 @@
+// Email address newtype which can only be created by parsing a valid address
+// Supports the commonly used subset of RFC 5322: dot-atom local parts and DNS domain names
+// Quoted local parts ("john doe"@example.com), comments and IP literal domains are rejected
+
+use std::fmt;
+use std::str::FromStr;
+
+// Length limits from RFC 5321, in bytes
+const MAX_ADDRESS_LEN: usize = 254;
+const MAX_LOCAL_LEN: usize = 64;
+const MAX_DOMAIN_LEN: usize = 253;
+const MAX_LABEL_LEN: usize = 63;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Email {
+    // Stored with the domain lowercased
+    address: String,
+    // Byte index of the '@' separator
+    at: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmailError {
+    Empty,
+    TooLong(usize),
+    MissingAt,
+    MultipleAt,
+    EmptyLocalPart,
+    LocalPartTooLong(usize),
+    InvalidLocalChar(char),
+    // Leading, trailing or consecutive dots in the local part
+    MisplacedDot,
+    EmptyDomain,
+    DomainTooLong(usize),
+    // Domain needs at least two labels like "example.com"
+    MissingTopLevelDomain,
+    EmptyLabel,
+    LabelTooLong(String),
+    InvalidDomainChar(char),
+    // Labels cannot start or end with '-'
+    MisplacedHyphen(String),
+    NumericTopLevelDomain(String),
+}
+
+impl fmt::Display for EmailError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmailError::Empty => write!(f, "address is empty"),
+            EmailError::TooLong(len) => {
+                write!(f, "address is {} bytes long, at most {} are allowed", len, MAX_ADDRESS_LEN)
+            }
+            EmailError::MissingAt => write!(f, "address is missing '@'"),
+            EmailError::MultipleAt => write!(f, "address contains more than one '@'"),
+            EmailError::EmptyLocalPart => write!(f, "nothing before '@'"),
+            EmailError::LocalPartTooLong(len) => {
+                write!(f, "local part is {} bytes long, at most {} are allowed", len, MAX_LOCAL_LEN)
+            }
+            EmailError::InvalidLocalChar(c) => write!(f, "local part cannot contain {:?}", c),
+            EmailError::MisplacedDot => {
+                write!(f, "local part cannot start or end with '.' or contain '..'")
+            }
+            EmailError::EmptyDomain => write!(f, "nothing after '@'"),
+            EmailError::DomainTooLong(len) => {
+                write!(f, "domain is {} bytes long, at most {} are allowed", len, MAX_DOMAIN_LEN)
+            }
+            EmailError::MissingTopLevelDomain => write!(f, "domain has no top-level domain"),
+            EmailError::EmptyLabel => write!(f, "domain contains an empty label"),
+            EmailError::LabelTooLong(label) => {
+                write!(f, "domain label {:?} is longer than {} bytes", label, MAX_LABEL_LEN)
+            }
+            EmailError::InvalidDomainChar(c) => write!(f, "domain cannot contain {:?}", c),
+            EmailError::MisplacedHyphen(label) => {
+                write!(f, "domain label {:?} cannot start or end with '-'", label)
+            }
+            EmailError::NumericTopLevelDomain(label) => {
+                write!(f, "top-level domain {:?} cannot be numeric", label)
+            }
+        }
+    }
+}
+
+impl Email {
+    pub fn parse(input: &str) -> Result<Email, EmailError> {
+        if input.is_empty() {
+            return Err(EmailError::Empty);
+        }
+        if input.len() > MAX_ADDRESS_LEN {
+            return Err(EmailError::TooLong(input.len()));
+        }
+
+        let at = input.find('@').ok_or(EmailError::MissingAt)?;
+        let (local, domain) = (&input[..at], &input[at + 1..]);
+        if domain.contains('@') {
+            return Err(EmailError::MultipleAt);
+        }
+
+        validate_local_part(local)?;
+        validate_domain(domain)?;
+
+        Ok(Email {
+            address: format!("{}@{}", local, domain.to_ascii_lowercase()),
+            at,
+        })
+    }
+
+    pub fn local_part(&self) -> &str {
+        &self.address[..self.at]
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.address[self.at + 1..]
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.address
+    }
+}
+
+fn validate_local_part(local: &str) -> Result<(), EmailError> {
+    if local.is_empty() {
+        return Err(EmailError::EmptyLocalPart);
+    }
+    if local.len() > MAX_LOCAL_LEN {
+        return Err(EmailError::LocalPartTooLong(local.len()));
+    }
+    // Characters allowed in an unquoted dot-atom ("atext" in RFC 5322)
+    if let Some(c) = local
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~-.".contains(*c)))
+    {
+        return Err(EmailError::InvalidLocalChar(c));
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return Err(EmailError::MisplacedDot);
+    }
+    Ok(())
+}
+
+fn validate_domain(domain: &str) -> Result<(), EmailError> {
+    if domain.is_empty() {
+        return Err(EmailError::EmptyDomain);
+    }
+    if domain.len() > MAX_DOMAIN_LEN {
+        return Err(EmailError::DomainTooLong(domain.len()));
+    }
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    for label in &labels {
+        if label.is_empty() {
+            return Err(EmailError::EmptyLabel);
+        }
+        if label.len() > MAX_LABEL_LEN {
+            return Err(EmailError::LabelTooLong(label.to_string()));
+        }
+        if let Some(c) = label.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '-')) {
+            return Err(EmailError::InvalidDomainChar(c));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(EmailError::MisplacedHyphen(label.to_string()));
+        }
+    }
+
+    if labels.len() < 2 {
+        return Err(EmailError::MissingTopLevelDomain);
+    }
+    let tld = labels[labels.len() - 1];
+    if tld.chars().all(|c| c.is_ascii_digit()) {
+        return Err(EmailError::NumericTopLevelDomain(tld.to_string()));
+    }
+    Ok(())
+}
+
+// Allows "someone@example.com".parse::<Email>()
+impl FromStr for Email {
+    type Err = EmailError;
+
+    fn from_str(s: &str) -> Result<Email, EmailError> {
+        Email::parse(s)
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.address)
+    }
+}
+
+// Domain of the given length in bytes, made of labels of at most 63 bytes and ending in ".com"
+fn domain_of_len(len: usize) -> String {
+    let mut domain = String::new();
+    let mut label = b'a';
+    while len - domain.len() > 4 + 64 {
+        domain += &(label as char).to_string().repeat(63);
+        domain.push('.');
+        label += 1;
+    }
+    domain += &(label as char).to_string().repeat(len - domain.len() - 4);
+    domain + ".com"
+}
+
+pub fn run() {
+    let inputs = [
+        "someone@Example.COM",
+        "first.last+tag@mail.example.org",
+        "no-at-sign.example.com",
+        "two@@example.com",
+        ".dot@example.com",
+        "someone@localhost",
+        "someone@-bad-.com",
+        "someone@example.123",
+        "some one@example.com",
+    ];
+
+    for input in inputs.iter() {
+        match Email::parse(input) {
+            Ok(email) => println!("{:35} => {} (local: {}, domain: {})", input, email, email.local_part(), email.domain()),
+            Err(e) => println!("{:35} => error: {}", input, e),
+        }
+    }
+
+    // Every error, and every length limit at and just over the limit
+    let local = |len: usize| "x".repeat(len);
+    let label = |len: usize| "l".repeat(len);
+    let expected: Vec<(String, Result<String, EmailError>)> = vec![
+        (String::from("someone@Example.COM"), Ok(String::from("someone@example.com"))),
+        (String::from("Some.One@EXAMPLE.com"), Ok(String::from("Some.One@example.com"))),
+        (String::new(), Err(EmailError::Empty)),
+        (String::from("no-at-sign.example.com"), Err(EmailError::MissingAt)),
+        (String::from("two@@example.com"), Err(EmailError::MultipleAt)),
+        (String::from("a@b@example.com"), Err(EmailError::MultipleAt)),
+        (String::from("@example.com"), Err(EmailError::EmptyLocalPart)),
+        (String::from("some one@example.com"), Err(EmailError::InvalidLocalChar(' '))),
+        (String::from("josé@example.com"), Err(EmailError::InvalidLocalChar('é'))),
+        (String::from(".dot@example.com"), Err(EmailError::MisplacedDot)),
+        (String::from("dot.@example.com"), Err(EmailError::MisplacedDot)),
+        (String::from("two..dots@example.com"), Err(EmailError::MisplacedDot)),
+        (String::from("someone@"), Err(EmailError::EmptyDomain)),
+        (String::from("someone@localhost"), Err(EmailError::MissingTopLevelDomain)),
+        (String::from("someone@example..com"), Err(EmailError::EmptyLabel)),
+        (String::from("someone@example.com."), Err(EmailError::EmptyLabel)),
+        (String::from("someone@exa_mple.com"), Err(EmailError::InvalidDomainChar('_'))),
+        (String::from("someone@-bad-.com"), Err(EmailError::MisplacedHyphen(String::from("-bad-")))),
+        (String::from("someone@example.123"), Err(EmailError::NumericTopLevelDomain(String::from("123")))),
+        (format!("{}@example.com", local(64)), Ok(format!("{}@example.com", local(64)))),
+        (format!("{}@example.com", local(65)), Err(EmailError::LocalPartTooLong(65))),
+        (format!("someone@{}.com", label(63)), Ok(format!("someone@{}.com", label(63)))),
+        (format!("someone@{}.com", label(64)), Err(EmailError::LabelTooLong(label(64)))),
+        (format!("{}@{}", local(64), domain_of_len(189)), Ok(format!("{}@{}", local(64), domain_of_len(189)))),
+        (format!("{}@{}", local(64), domain_of_len(190)), Err(EmailError::TooLong(255))),
+        // A domain of 253 bytes leaves no room for a local part within 254 bytes
+        (format!("a@{}", domain_of_len(253)), Err(EmailError::TooLong(255))),
+    ];
+    for (input, result) in expected {
+        let parsed = Email::parse(&input).map(|email| email.to_string());
+        assert_eq!(parsed, result, "{:?}", input);
+    }
+
+    // So the domain limit can only be seen by checking the domain on its own
+    assert_eq!(domain_of_len(253).len(), 253);
+    assert_eq!(validate_domain(&domain_of_len(253)), Ok(()));
+    assert_eq!(validate_domain(&domain_of_len(254)), Err(EmailError::DomainTooLong(254)));
+    assert_eq!(EmailError::TooLong(255).to_string(), "address is 255 bytes long, at most 254 are allowed");
+
+    let email = Email::parse("Some.One@Mail.Example.ORG").unwrap();
+    assert_eq!((email.local_part(), email.domain()), ("Some.One", "mail.example.org"));
+}
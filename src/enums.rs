@@ -1,5 +1,10 @@
 
+use crate::options;
 use std::any::type_name;
+use std::fmt;
+use std::net;
+use std::str::FromStr;
+
 fn type_of<T>(_: T) -> &'static str {
     type_name::<T>()
 }
@@ -13,14 +18,199 @@ enum IpAddrKind {
 }
 
 // Enums can store data too
-enum IpAddr {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IpAddr {
     // Struct tuple variants
     V4(u8,u8,u8,u8),
-    V6(String),
+    // Eight 16-bit groups
+    V6([u16; 8]),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AddrParseError {
+    Empty,
+    // Dotted quads need exactly 4 octets
+    OctetCount(usize),
+    // Octets are decimal numbers between 0 and 255 without leading zeros
+    InvalidOctet(String),
+    // Without "::" an IPv6 address needs exactly 8 groups, with "::" at most 7
+    GroupCount(usize),
+    // Groups are 1 to 4 hex digits
+    InvalidGroup(String),
+    // "::" can only appear once
+    MultipleCompressions,
+}
+
+impl fmt::Display for AddrParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AddrParseError::Empty => write!(f, "address is empty"),
+            AddrParseError::OctetCount(count) => write!(f, "expected 4 octets but found {}", count),
+            AddrParseError::InvalidOctet(s) => write!(f, "{:?} is not an octet between 0 and 255", s),
+            AddrParseError::GroupCount(count) => write!(f, "{} groups do not make an IPv6 address", count),
+            AddrParseError::InvalidGroup(s) => write!(f, "{:?} is not a group of 1 to 4 hex digits", s),
+            AddrParseError::MultipleCompressions => write!(f, "\"::\" can only be used once"),
+        }
+    }
+}
+
+fn parse_v4(s: &str) -> Result<[u8; 4], AddrParseError> {
+    let parts: Vec<&str> = s.split('.').collect();
+    if parts.len() != 4 {
+        return Err(AddrParseError::OctetCount(parts.len()));
+    }
+
+    let mut octets = [0u8; 4];
+    for (octet, part) in octets.iter_mut().zip(parts.iter()) {
+        // Leading zeros are rejected since some tools read "010" as octal
+        let valid = !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()) && (part.len() == 1 || !part.starts_with('0'));
+        *octet = match part.parse() {
+            Ok(value) if valid => value,
+            _ => return Err(AddrParseError::InvalidOctet(part.to_string())),
+        };
+    }
+    Ok(octets)
+}
+
+// Groups of one side of "::", the last group may be a dotted quad standing for two groups
+fn parse_groups(s: &str, may_end_with_v4: bool) -> Result<Vec<u16>, AddrParseError> {
+    let mut groups = Vec::new();
+    if s.is_empty() {
+        return Ok(groups);
+    }
+
+    let parts: Vec<&str> = s.split(':').collect();
+    for (i, part) in parts.iter().enumerate() {
+        if may_end_with_v4 && i == parts.len() - 1 && part.contains('.') {
+            let [a, b, c, d] = parse_v4(part)?;
+            groups.push(u16::from_be_bytes([a, b]));
+            groups.push(u16::from_be_bytes([c, d]));
+        } else if part.is_empty() || part.len() > 4 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(AddrParseError::InvalidGroup(part.to_string()));
+        } else {
+            groups.push(u16::from_str_radix(part, 16).unwrap());
+        }
+    }
+    Ok(groups)
+}
+
+fn parse_v6(s: &str) -> Result<[u16; 8], AddrParseError> {
+    let mut groups = [0u16; 8];
+    match s.find("::") {
+        None => {
+            let parsed = parse_groups(s, true)?;
+            if parsed.len() != 8 {
+                return Err(AddrParseError::GroupCount(parsed.len()));
+            }
+            groups.copy_from_slice(&parsed);
+        }
+        Some(i) => {
+            let (head, tail) = (&s[..i], &s[i + 2..]);
+            if tail.contains("::") {
+                return Err(AddrParseError::MultipleCompressions);
+            }
+            let head = parse_groups(head, false)?;
+            let tail = parse_groups(tail, true)?;
+            // "::" stands for at least one zero group
+            if head.len() + tail.len() > 7 {
+                return Err(AddrParseError::GroupCount(head.len() + tail.len()));
+            }
+            groups[..head.len()].copy_from_slice(&head);
+            groups[8 - tail.len()..].copy_from_slice(&tail);
+        }
+    }
+    Ok(groups)
+}
+
+// Accepts dotted quads like "127.0.0.1" and IPv6 addresses, full or compressed with "::",
+// including an IPv4 address in the last 32 bits like "::ffff:192.0.2.1"
+impl FromStr for IpAddr {
+    type Err = AddrParseError;
+
+    fn from_str(s: &str) -> Result<IpAddr, AddrParseError> {
+        let s = s.trim();
+        if s.is_empty() {
+            Err(AddrParseError::Empty)
+        } else if s.contains(':') {
+            parse_v6(s).map(IpAddr::V6)
+        } else {
+            let [a, b, c, d] = parse_v4(s)?;
+            Ok(IpAddr::V4(a, b, c, d))
+        }
+    }
+}
+
+// IPv6 is written in the canonical form of RFC 5952: lowercase hex without leading zeros,
+// the longest run of two or more zero groups (the first one on a tie) replaced by "::",
+// and IPv4-mapped addresses (::ffff:0:0/96) ending in a dotted quad
+impl fmt::Display for IpAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let groups = match self {
+            IpAddr::V4(a, b, c, d) => return write!(f, "{}.{}.{}.{}", a, b, c, d),
+            IpAddr::V6(groups) => groups,
+        };
+
+        if groups[..5] == [0; 5] && groups[5] == 0xffff {
+            let [a, b] = groups[6].to_be_bytes();
+            let [c, d] = groups[7].to_be_bytes();
+            return write!(f, "::ffff:{}.{}.{}.{}", a, b, c, d);
+        }
+
+        // Longest run of zero groups as (start, length)
+        let mut longest = (0, 0);
+        let mut start = 0;
+        for (i, &group) in groups.iter().enumerate() {
+            if group != 0 {
+                start = i + 1;
+            } else if i + 1 - start > longest.1 {
+                longest = (start, i + 1 - start);
+            }
+        }
+
+        let write_groups = |f: &mut fmt::Formatter, groups: &[u16]| -> fmt::Result {
+            for (i, group) in groups.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ":")?;
+                }
+                write!(f, "{:x}", group)?;
+            }
+            Ok(())
+        };
+        match longest {
+            (start, len) if len >= 2 => {
+                write_groups(f, &groups[..start])?;
+                write!(f, "::")?;
+                write_groups(f, &groups[start + len..])
+            }
+            _ => write_groups(f, groups),
+        }
+    }
+}
+
+impl From<net::IpAddr> for IpAddr {
+    fn from(addr: net::IpAddr) -> IpAddr {
+        match addr {
+            net::IpAddr::V4(v4) => {
+                let [a, b, c, d] = v4.octets();
+                IpAddr::V4(a, b, c, d)
+            }
+            net::IpAddr::V6(v6) => IpAddr::V6(v6.segments()),
+        }
+    }
+}
+
+impl From<IpAddr> for net::IpAddr {
+    fn from(addr: IpAddr) -> net::IpAddr {
+        match addr {
+            IpAddr::V4(a, b, c, d) => net::IpAddr::V4(net::Ipv4Addr::new(a, b, c, d)),
+            IpAddr::V6(g) => net::IpAddr::V6(net::Ipv6Addr::new(g[0], g[1], g[2], g[3], g[4], g[5], g[6], g[7])),
+        }
+    }
 }
 
 // Enum with varied type of variants
-enum Message {
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
     // Unit-like variant
     Quit,
     
@@ -31,17 +221,14 @@ enum Message {
     Write (String),
     // A different Struct tuple variant
     ChangeColor(i32, i32, i32),
-
-    // Useless variants
-    Random1,
-    Random2(i32),
 }
 
 impl Message {
 
     // Self will be reference to the enum instance and hence will be one of the variants
     fn call(&self) -> i32 {
-        // Match will do pattern matching on the variant types 
+        // Match will do pattern matching on the variant types
+        // Every variant is listed, so adding a new one won't compile until it is handled here
         match self{
             Message::Quit => 0,
             Message::Move{x:a,y:b} => a+b,
@@ -49,7 +236,6 @@ impl Message {
             Message::Write (text) => {
                 text.len() as i32
             },
-            _ => -1, // Placeholder for remaining variant types
         }
     }
 }
@@ -66,7 +252,69 @@ pub fn run(){
 
     // Different variants can have variant types of different parameter types
     let home = IpAddr::V4(127,0,0,1);
-    let loopback = IpAddr::V6(String::from("::1"));
+    let loopback = IpAddr::V6([0, 0, 0, 0, 0, 0, 0, 1]);
+    println!("home: {}, loopback: {}", home, loopback);
+
+    // Parsing with FromStr and printing with Display, the canonical form of each address is on the right
+    let table = [
+        ("0.0.0.0", "0.0.0.0"),
+        ("255.255.255.255", "255.255.255.255"),
+        ("192.168.1.10", "192.168.1.10"),
+        ("::", "::"),
+        ("::1", "::1"),
+        ("1::", "1::"),
+        ("0:0:0:0:0:0:0:1", "::1"),
+        ("2001:0DB8:0000:0000:0000:0000:0000:0001", "2001:db8::1"),
+        ("2001:db8:0:0:1:0:0:1", "2001:db8::1:0:0:1"),
+        ("2001:db8:0:1:1:1:1:1", "2001:db8:0:1:1:1:1:1"),
+        ("2001:0:0:1:0:0:0:1", "2001:0:0:1::1"),
+        ("2001:db8::0:1", "2001:db8::1"),
+        ("1:2:3:4:5:6:7::", "1:2:3:4:5:6:7:0"),
+        ("::2:3:4:5:6:7:8", "0:2:3:4:5:6:7:8"),
+        ("fe80::1:2", "fe80::1:2"),
+        ("::ffff:192.0.2.128", "::ffff:192.0.2.128"),
+        ("::ffff:c000:280", "::ffff:192.0.2.128"),
+        ("64:ff9b::192.0.2.33", "64:ff9b::c000:221"),
+        ("::192.0.2.1", "::c000:201"),
+    ];
+    for &(input, canonical) in table.iter() {
+        let addr: IpAddr = input.parse().unwrap();
+        assert_eq!(addr.to_string(), canonical);
+        // The canonical form matches the standard library and parses back to the same address
+        let std_addr: net::IpAddr = input.parse().unwrap();
+        assert_eq!(IpAddr::from(std_addr), addr);
+        assert_eq!(std_addr.to_string(), canonical);
+        assert_eq!(canonical.parse::<IpAddr>(), Ok(addr));
+        assert_eq!(net::IpAddr::from(addr), std_addr);
+    }
+
+    let invalid = [
+        ("", AddrParseError::Empty),
+        ("1.2.3", AddrParseError::OctetCount(3)),
+        ("1.2.3.4.5", AddrParseError::OctetCount(5)),
+        ("1.2.3.256", AddrParseError::InvalidOctet(String::from("256"))),
+        ("1.2.3.04", AddrParseError::InvalidOctet(String::from("04"))),
+        ("1.2..4", AddrParseError::InvalidOctet(String::new())),
+        ("1.2.3.+4", AddrParseError::InvalidOctet(String::from("+4"))),
+        ("1:2:3:4:5:6:7", AddrParseError::GroupCount(7)),
+        ("1:2:3:4:5:6:7:8:9", AddrParseError::GroupCount(9)),
+        ("1:2:3:4::5:6:7:8", AddrParseError::GroupCount(8)),
+        ("1::2::3", AddrParseError::MultipleCompressions),
+        (":::", AddrParseError::InvalidGroup(String::new())),
+        ("1:2:3:4:5:6:7:", AddrParseError::InvalidGroup(String::new())),
+        (":1:2:3:4:5:6:7", AddrParseError::InvalidGroup(String::new())),
+        ("12345::", AddrParseError::InvalidGroup(String::from("12345"))),
+        ("g::", AddrParseError::InvalidGroup(String::from("g"))),
+        ("1.2.3.4::", AddrParseError::InvalidGroup(String::from("1.2.3.4"))),
+        ("::1.2.3.4:5", AddrParseError::InvalidGroup(String::from("1.2.3.4"))),
+        ("::ffff:1.2.3", AddrParseError::OctetCount(3)),
+        ("1:2:3:4:5:6:7:1.2.3.4", AddrParseError::GroupCount(9)),
+    ];
+    for (input, error) in invalid.iter() {
+        assert_eq!(input.parse::<IpAddr>().as_ref(), Err(error));
+        assert!(input.parse::<net::IpAddr>().is_err());
+    }
+    println!("{}", "1::2::3".parse::<IpAddr>().unwrap_err());
 
     // Enums can also implement methods
     let msg = Message::Move{x:3,y:4};
@@ -85,7 +333,8 @@ pub fn run(){
     let absent_number: Option<i32> = None;
     
     println!("Type of some_string: {} \nType of absent_number: {}", type_of(some_string),type_of(absent_number));
-    println!("Addition of some_number: {:?} \nType of some_number: {}", add(some_number,6), type_of(some_number));
+    // options::add adds to the value inside without a hand-written match, see options.rs for more Option combinators
+    println!("Addition of some_number: {:?} \nType of some_number: {}", options::add(some_number,6), type_of(some_number));
 
 
     // Using 'match' is exhaustive
@@ -108,10 +357,3 @@ pub fn run(){
 
 }
 
-
-fn add(opt: Option<i32>, delta: i32) -> Option<i32> {
-    match opt {
-        Some(i) => Some(i+delta),
-        None => None,
-    }
-}
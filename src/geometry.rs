@@ -0,0 +1,370 @@
+// Shape trait and the 2D shapes implementing it
+// rectangles::Rectangle implements Shape too, see rectangles.rs
+
+use std::f64::consts::PI;
+use std::fmt;
+
+// Smallest axis-aligned box containing a shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    // Box around all the given points, None when there are no points
+    pub fn around(points: &[(f64, f64)]) -> Option<BoundingBox> {
+        let (&(x, y), rest) = points.split_first()?;
+        let mut bbox = BoundingBox { min_x: x, min_y: y, max_x: x, max_y: y };
+        for &(x, y) in rest {
+            bbox.min_x = bbox.min_x.min(x);
+            bbox.min_y = bbox.min_y.min(y);
+            bbox.max_x = bbox.max_x.max(x);
+            bbox.max_y = bbox.max_y.max(y);
+        }
+        Some(bbox)
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    // Smallest box containing both boxes
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+}
+
+// Behaviour shared by all shapes
+// Debug is a supertrait so that trait objects like Box<dyn Shape> can be printed
+// Points on the edge of a shape count as contained in it
+pub trait Shape: fmt::Debug {
+    fn name(&self) -> &'static str;
+    fn area(&self) -> f64;
+    fn perimeter(&self) -> f64;
+    fn bounding_box(&self) -> BoundingBox;
+    fn contains(&self, x: f64, y: f64) -> bool;
+
+    // Points along the outline, defaults to the corners of the bounding box which suits axis-aligned rectangles
+    fn outline(&self) -> Vec<(f64, f64)> {
+        let b = self.bounding_box();
+        vec![(b.min_x, b.min_y), (b.max_x, b.min_y), (b.max_x, b.max_y), (b.min_x, b.max_y)]
+    }
+
+    // Whether 'other' lies entirely inside this shape
+    // Checks the points of the outline of 'other', which is exact when this shape is convex
+    // A concave shape can reach into 'other' between those points, so Polygon overrides this
+    fn contains_shape(&self, other: &dyn Shape) -> bool {
+        other.outline().iter().all(|&(x, y)| self.contains(x, y))
+    }
+}
+
+fn distance((x1, y1): (f64, f64), (x2, y2): (f64, f64)) -> f64 {
+    ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt()
+}
+
+// Square with its top-left corner at (x, y)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Square {
+    pub x: f64,
+    pub y: f64,
+    pub side: f64,
+}
+
+impl Shape for Square {
+    fn name(&self) -> &'static str {
+        "square"
+    }
+
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+
+    fn perimeter(&self) -> f64 {
+        4.0 * self.side
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox { min_x: self.x, min_y: self.y, max_x: self.x + self.side, max_y: self.y + self.side }
+    }
+
+    fn contains(&self, x: f64, y: f64) -> bool {
+        self.bounding_box().contains(x, y)
+    }
+}
+
+const CIRCLE_OUTLINE_POINTS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circle {
+    pub center: (f64, f64),
+    pub radius: f64,
+}
+
+impl Shape for Circle {
+    fn name(&self) -> &'static str {
+        "circle"
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * PI * self.radius
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let (x, y) = self.center;
+        BoundingBox {
+            min_x: x - self.radius,
+            min_y: y - self.radius,
+            max_x: x + self.radius,
+            max_y: y + self.radius,
+        }
+    }
+
+    fn contains(&self, x: f64, y: f64) -> bool {
+        distance(self.center, (x, y)) <= self.radius
+    }
+
+    // Points spread evenly around the circle
+    fn outline(&self) -> Vec<(f64, f64)> {
+        let (x, y) = self.center;
+        (0..CIRCLE_OUTLINE_POINTS)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / CIRCLE_OUTLINE_POINTS as f64;
+                (x + self.radius * angle.cos(), y + self.radius * angle.sin())
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triangle {
+    pub a: (f64, f64),
+    pub b: (f64, f64),
+    pub c: (f64, f64),
+}
+
+// Twice the signed area of the triangle (p, q, r), positive when the points go counter-clockwise
+fn cross((px, py): (f64, f64), (qx, qy): (f64, f64), (rx, ry): (f64, f64)) -> f64 {
+    (qx - px) * (ry - py) - (qy - py) * (rx - px)
+}
+
+// Whether the segments p-q and r-s cross each other, touching at an end or along a line doesn't count
+fn segments_cross(p: (f64, f64), q: (f64, f64), r: (f64, f64), s: (f64, f64)) -> bool {
+    cross(p, q, r) * cross(p, q, s) < 0.0 && cross(r, s, p) * cross(r, s, q) < 0.0
+}
+
+impl Shape for Triangle {
+    fn name(&self) -> &'static str {
+        "triangle"
+    }
+
+    fn area(&self) -> f64 {
+        cross(self.a, self.b, self.c).abs() / 2.0
+    }
+
+    fn perimeter(&self) -> f64 {
+        distance(self.a, self.b) + distance(self.b, self.c) + distance(self.c, self.a)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::around(&[self.a, self.b, self.c]).unwrap()
+    }
+
+    // The point is inside when it is on the same side of all three edges
+    fn contains(&self, x: f64, y: f64) -> bool {
+        let p = (x, y);
+        let d1 = cross(self.a, self.b, p);
+        let d2 = cross(self.b, self.c, p);
+        let d3 = cross(self.c, self.a, p);
+        let has_negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+        let has_positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+        !(has_negative && has_positive)
+    }
+
+    fn outline(&self) -> Vec<(f64, f64)> {
+        vec![self.a, self.b, self.c]
+    }
+}
+
+// Simple (non self-intersecting) polygon given by its vertices in order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    points: Vec<(f64, f64)>,
+}
+
+impl Polygon {
+    // A polygon needs at least 3 vertices
+    pub fn new(points: Vec<(f64, f64)>) -> Option<Polygon> {
+        if points.len() < 3 {
+            None
+        } else {
+            Some(Polygon { points })
+        }
+    }
+
+    // Pairs of consecutive vertices, including the edge from the last vertex back to the first
+    fn edges(&self) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+        self.points.iter().copied().zip(self.points.iter().copied().cycle().skip(1))
+    }
+}
+
+impl Shape for Polygon {
+    fn name(&self) -> &'static str {
+        "polygon"
+    }
+
+    // Shoelace formula
+    fn area(&self) -> f64 {
+        let twice_area: f64 = self.edges().map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1).sum();
+        twice_area.abs() / 2.0
+    }
+
+    fn perimeter(&self) -> f64 {
+        self.edges().map(|(p, q)| distance(p, q)).sum()
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::around(&self.points).unwrap()
+    }
+
+    // Ray casting: a ray from the point crosses the edges an odd number of times when the point is inside
+    fn contains(&self, x: f64, y: f64) -> bool {
+        let mut inside = false;
+        for (p, q) in self.edges() {
+            // Points on an edge are inside
+            let on_segment = cross(p, q, (x, y)) == 0.0
+                && x >= p.0.min(q.0)
+                && x <= p.0.max(q.0)
+                && y >= p.1.min(q.1)
+                && y <= p.1.max(q.1);
+            if on_segment {
+                return true;
+            }
+            if (p.1 > y) != (q.1 > y) && x < p.0 + (y - p.1) * (q.0 - p.0) / (q.1 - p.1) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    fn outline(&self) -> Vec<(f64, f64)> {
+        self.points.clone()
+    }
+
+    // Besides every point of the outline of 'other' being inside, no edge of this polygon may cross that outline,
+    // which it does when a concave part of the polygon reaches into 'other'
+    fn contains_shape(&self, other: &dyn Shape) -> bool {
+        let outline = other.outline();
+        let mut other_edges = outline.iter().copied().zip(outline.iter().copied().cycle().skip(1));
+        outline.iter().all(|&(x, y)| self.contains(x, y))
+            && other_edges.all(|(r, s)| self.edges().all(|(p, q)| !segments_cross(p, q, r, s)))
+    }
+}
+
+// A collection of different shapes stored as trait objects
+#[derive(Debug, Default)]
+pub struct Scene {
+    pub shapes: Vec<Box<dyn Shape>>,
+}
+
+impl Scene {
+    pub fn new() -> Scene {
+        Scene::default()
+    }
+
+    pub fn add(&mut self, shape: Box<dyn Shape>) {
+        self.shapes.push(shape);
+    }
+
+    // Sum of the areas, overlapping parts are counted once for every shape
+    pub fn total_area(&self) -> f64 {
+        self.shapes.iter().map(|shape| shape.area()).sum()
+    }
+
+    // Box around every shape of the scene, None for an empty scene
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut boxes = self.shapes.iter().map(|shape| shape.bounding_box());
+        let first = boxes.next()?;
+        Some(boxes.fold(first, |acc, bbox| acc.union(&bbox)))
+    }
+
+    // Shapes containing the given point
+    pub fn shapes_at(&self, x: f64, y: f64) -> Vec<&dyn Shape> {
+        self.shapes.iter().filter(|shape| shape.contains(x, y)).map(|shape| shape.as_ref()).collect()
+    }
+
+    // Largest shapes come first
+    pub fn sort_by_area(&mut self) {
+        self.shapes.sort_by(|a, b| b.area().total_cmp(&a.area()));
+    }
+}
+
+pub fn run() {
+    use crate::rectangles::Rectangle;
+
+    let mut scene = Scene::new();
+    scene.add(Box::new(Rectangle::new(30, 60)));
+    scene.add(Box::new(Square { x: 10.0, y: 10.0, side: 20.0 }));
+    scene.add(Box::new(Circle { center: (50.0, 50.0), radius: 10.0 }));
+    scene.add(Box::new(Triangle { a: (0.0, 0.0), b: (40.0, 0.0), c: (0.0, 30.0) }));
+    scene.add(Box::new(Polygon::new(vec![(60.0, 0.0), (80.0, 0.0), (90.0, 20.0), (70.0, 30.0)]).unwrap()));
+    assert!(Polygon::new(vec![(0.0, 0.0), (1.0, 1.0)]).is_none());
+
+    // Each call is dispatched to the implementation of the actual shape at runtime
+    scene.sort_by_area();
+    for shape in &scene.shapes {
+        println!("{:8} area: {:8.2} perimeter: {:7.2} {:?}", shape.name(), shape.area(), shape.perimeter(), shape.bounding_box());
+    }
+    println!("Total area: {:.2}", scene.total_area());
+    let rounded = |x: f64| (x * 100.0).round() / 100.0;
+    let sorted: Vec<(&str, f64, f64)> =
+        scene.shapes.iter().map(|shape| (shape.name(), rounded(shape.area()), rounded(shape.perimeter()))).collect();
+    assert_eq!(
+        sorted,
+        [
+            ("rectangle", 1800.0, 180.0),
+            ("triangle", 600.0, 120.0),
+            ("polygon", 550.0, 96.34),
+            ("square", 400.0, 80.0),
+            ("circle", 314.16, 62.83),
+        ]
+    );
+    assert_eq!(rounded(scene.total_area()), 3664.16);
+
+    // The scene's box is the union of every shape's box
+    let bounds = scene.bounding_box().unwrap();
+    assert_eq!(bounds, BoundingBox { min_x: 0.0, min_y: 0.0, max_x: 90.0, max_y: 60.0 });
+    assert_eq!((bounds.width(), bounds.height()), (90.0, 60.0));
+    assert!(Scene::new().bounding_box().is_none());
+
+    // Points on an edge count as inside
+    let names: Vec<&str> = scene.shapes_at(15.0, 15.0).iter().map(|shape| shape.name()).collect();
+    println!("Shapes at (15, 15): {:?}", names);
+    assert_eq!(names, ["rectangle", "triangle", "square"]);
+    let names: Vec<&str> = scene.shapes_at(20.0, 15.0).iter().map(|shape| shape.name()).collect();
+    assert_eq!(names, ["rectangle", "triangle", "square"]);
+    let names: Vec<&str> = scene.shapes_at(60.0, 50.0).iter().map(|shape| shape.name()).collect();
+    assert_eq!(names, ["circle"]);
+    let names: Vec<&str> = scene.shapes_at(75.0, 15.0).iter().map(|shape| shape.name()).collect();
+    assert_eq!(names, ["polygon"]);
+    assert!(scene.shapes_at(60.0, 30.0).is_empty());
+}
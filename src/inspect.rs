@@ -0,0 +1,152 @@
+// Looks inside a string at its three levels:
+//   bytes      what the string is stored as, 1 to 4 UTF-8 bytes per char
+//   chars      Unicode scalar values, written U+XXXX
+//   graphemes  what a reader sees as one character, which can be several chars like "स्ते" or "👍🏽"
+//
+// std has no grapheme segmentation, so graphemes() follows the main rules of UAX #29 for the common cases:
+// combining marks, joiners, emoji modifiers, flags and Devanagari conjuncts stay with the char before them
+
+use std::fmt::Write;
+
+pub fn is_extender(c: char) -> bool {
+    matches!(c,
+        // Combining marks of Latin, Greek, Cyrillic, Hebrew and Arabic
+        '\u{300}'..='\u{36f}' | '\u{483}'..='\u{489}' | '\u{591}'..='\u{5bd}' | '\u{610}'..='\u{61a}' | '\u{64b}'..='\u{65f}'
+        // Devanagari signs and vowel signs, including the virama
+        | '\u{900}'..='\u{903}' | '\u{93a}'..='\u{93c}' | '\u{93e}'..='\u{94f}' | '\u{951}'..='\u{957}' | '\u{962}'..='\u{963}'
+        // Combining marks supplements and variation selectors
+        | '\u{1ab0}'..='\u{1aff}' | '\u{1dc0}'..='\u{1dff}' | '\u{20d0}'..='\u{20ff}' | '\u{fe00}'..='\u{fe0f}' | '\u{fe20}'..='\u{fe2f}'
+        // Zero width non-joiner and joiner
+        | '\u{200c}' | '\u{200d}'
+        // Emoji skin tones, tags and variation selectors supplement
+        | '\u{1f3fb}'..='\u{1f3ff}' | '\u{e0020}'..='\u{e007f}' | '\u{e0100}'..='\u{e01ef}')
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+fn is_devanagari_consonant(c: char) -> bool {
+    matches!(c, '\u{915}'..='\u{939}' | '\u{958}'..='\u{95f}' | '\u{978}'..='\u{97f}')
+}
+
+const VIRAMA: char = '\u{94d}';
+const ZWJ: char = '\u{200d}';
+
+// Whether 'c' continues the grapheme made of 'current' instead of starting a new one
+fn continues(current: &str, c: char) -> bool {
+    let last = match current.chars().next_back() {
+        Some(last) => last,
+        None => return false,
+    };
+    // "\r\n" is a single grapheme, any other control char stands on its own
+    if last == '\r' && c == '\n' {
+        return true;
+    }
+    if last.is_control() || c.is_control() {
+        return false;
+    }
+    if is_extender(c) {
+        return true;
+    }
+    // Emoji joined by a zero width joiner, like 👩‍💻
+    if last == ZWJ {
+        return true;
+    }
+    // Flags are pairs of regional indicators
+    if is_regional_indicator(c) && is_regional_indicator(last) {
+        return current.chars().filter(|&c| is_regional_indicator(c)).count() % 2 == 1;
+    }
+    // Consonant + virama + consonant is a conjunct, like स्त
+    is_devanagari_consonant(c) && last == VIRAMA
+}
+
+// Extended grapheme clusters of the string, each a slice of it
+pub fn graphemes(s: &str) -> Vec<&str> {
+    let mut clusters = Vec::new();
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if i > start && !continues(&s[start..i], c) {
+            clusters.push(&s[start..i]);
+            start = i;
+        }
+    }
+    if start < s.len() {
+        clusters.push(&s[start..]);
+    }
+    clusters
+}
+
+// Byte offsets where the string can be sliced without panicking, from 0 to s.len()
+pub fn char_boundaries(s: &str) -> Vec<usize> {
+    (0..=s.len()).filter(|&i| s.is_char_boundary(i)).collect()
+}
+
+// Chars start..end of the string, counted in chars rather than bytes
+// Never panics: indices past the end are cut to the end and start >= end gives ""
+pub fn slice_chars(s: &str, start: usize, end: usize) -> &str {
+    if start >= end {
+        return "";
+    }
+    let byte_offset = |n: usize| s.char_indices().nth(n).map_or(s.len(), |(i, _)| i);
+    let start = byte_offset(start);
+    let end = byte_offset(end);
+    &s[start..end]
+}
+
+// Table with one row per char: byte range, UTF-8 bytes in hex, the char, its code point and its grapheme
+pub fn inspect(s: &str) -> String {
+    let mut out = String::new();
+    writeln!(out, "{:<9} {:<12} {:<9} {:<10} grapheme", "bytes", "hex", "char", "code point").unwrap();
+
+    for (g, grapheme) in graphemes(s).iter().enumerate() {
+        let grapheme_start = grapheme.as_ptr() as usize - s.as_ptr() as usize;
+        for (i, c) in grapheme.char_indices() {
+            let start = grapheme_start + i;
+            let end = start + c.len_utf8();
+            let hex: Vec<String> = s.as_bytes()[start..end].iter().map(|b| format!("{:02x}", b)).collect();
+            // Control chars and combining marks on their own would mess up the table, so they are shown escaped
+            let shown = if c.is_control() || is_extender(c) { c.escape_unicode().to_string() } else { c.to_string() };
+            let range = format!("{}..{}", start, end);
+            let label = match i {
+                0 if grapheme.chars().any(char::is_control) => format!("{} {:?}", g, grapheme),
+                0 => format!("{} {}", g, grapheme),
+                _ => String::new(),
+            };
+            let row = format!("{:<9} {:<12} {:<9} U+{:<8X} {}", range, hex.join(" "), shown, c as u32, label);
+            writeln!(out, "{}", row.trim_end()).unwrap();
+        }
+    }
+
+    writeln!(out, "{} bytes, {} chars, {} graphemes", s.len(), s.chars().count(), graphemes(s).len()).unwrap();
+    writeln!(out, "Valid slice boundaries: {:?}", char_boundaries(s)).unwrap();
+    out
+}
+
+// 'inspect' command: table of the bytes, chars and graphemes of the arguments joined by spaces
+pub fn explore(args: &[String]) -> Result<(), String> {
+    if args.is_empty() {
+        return Err(String::from("usage: inspect <text>"));
+    }
+    print!("{}", inspect(&args.join(" ")));
+    Ok(())
+}
+
+pub fn run() {
+    print!("{}", inspect("नमस्ते"));
+
+    assert_eq!(graphemes("नमस्ते"), ["न", "म", "स्ते"]);
+    assert_eq!(graphemes("e\u{301}a"), ["e\u{301}", "a"]);
+    assert_eq!(graphemes("👍🏽👩\u{200d}💻"), ["👍🏽", "👩\u{200d}💻"]);
+    assert_eq!(graphemes("🇮🇳🇯🇵🇫"), ["🇮🇳", "🇯🇵", "🇫"]);
+    assert_eq!(graphemes("a\r\nb"), ["a", "\r\n", "b"]);
+    assert_eq!(char_boundaries("aé"), [0, 1, 3]);
+
+    // &s[0..1] would panic in the middle of 'न', slice_chars counts whole chars instead
+    let s = "नमस्ते";
+    assert_eq!(slice_chars(s, 0, 2), "नम");
+    assert_eq!(slice_chars(s, 4, 100), "ते");
+    assert_eq!(slice_chars(s, 3, 1), "");
+    assert_eq!(slice_chars(s, 10, 20), "");
+    println!("s.get(0..1) of {}: {:?}", s, s.get(0..1));
+}
@@ -0,0 +1,406 @@
+// A small hand-written JSON encoder and decoder (RFC 8259)
+
+use std::fmt;
+
+// Arrays and objects nested deeper than this are rejected instead of running out of stack
+pub const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    // Numbers keep their original text so that large integers are not rounded through f64
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    // Keys keep the order in which they were written
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Value {
+        Value::Number(n.to_string())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+}
+
+// Writes the string with quotes, escaping only what JSON requires
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            '\u{8}' => write!(f, "\\b")?,
+            '\u{c}' => write!(f, "\\f")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            // Everything else, including non-ASCII characters, is written as UTF-8
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+// Compact encoding without any whitespace
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write_string(f, s),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+// Element of the top-level array along with where it starts in the input
+#[derive(Debug, PartialEq)]
+pub struct Element {
+    // 1-based line and column (in characters) of the element's first character
+    pub line: usize,
+    pub column: usize,
+    pub value: Value,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+// Recursive descent parser over the characters of the input
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+    // Arrays and objects which are currently open
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: String) -> Result<T, ParseError> {
+        Err(ParseError { line: self.line, column: self.column, message })
+    }
+
+    // Only whitespace may follow the value
+    fn finish<T>(&mut self, value: T) -> Result<T, ParseError> {
+        self.skip_whitespace();
+        match self.next() {
+            None => Ok(value),
+            Some(c) => self.error(format!("unexpected {:?} after the value", c)),
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => self.error(format!("expected {:?} but found {:?}", expected, c)),
+            None => self.error(format!("expected {:?} but input ended", expected)),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('n') => self.parse_literal("null", Value::Null),
+            Some('t') => self.parse_literal("true", Value::Bool(true)),
+            Some('f') => self.parse_literal("false", Value::Bool(false)),
+            Some('"') => Ok(Value::String(self.parse_string()?)),
+            Some('[') => self.nested(Parser::parse_array),
+            Some('{') => self.nested(Parser::parse_object),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.parse_number(),
+            Some(c) => {
+                self.next();
+                self.error(format!("unexpected {:?}", c))
+            }
+            None => self.error("expected a value but input ended".to_string()),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Parser<'a>) -> Result<Value, ParseError>) -> Result<Value, ParseError> {
+        if self.depth == MAX_DEPTH {
+            return self.error(format!("arrays and objects cannot be nested more than {} deep", MAX_DEPTH));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: Value) -> Result<Value, ParseError> {
+        for expected in literal.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    // Appends consecutive digits to 'text' and returns how many there were
+    fn parse_digits(&mut self, text: &mut String) -> usize {
+        let mut count = 0;
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_digit()) {
+            text.push(c);
+            self.next();
+            count += 1;
+        }
+        count
+    }
+
+    fn parse_number(&mut self) -> Result<Value, ParseError> {
+        let mut text = String::new();
+        if self.peek() == Some('-') {
+            text.push('-');
+            self.next();
+        }
+        // Integer part cannot have leading zeros
+        if self.peek() == Some('0') {
+            text.push('0');
+            self.next();
+            if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return self.error("numbers cannot have leading zeros".to_string());
+            }
+        } else if self.parse_digits(&mut text) == 0 {
+            return self.error("expected digits in number".to_string());
+        }
+        if self.peek() == Some('.') {
+            text.push('.');
+            self.next();
+            if self.parse_digits(&mut text) == 0 {
+                return self.error("expected digits after decimal point".to_string());
+            }
+        }
+        if let Some(e) = self.peek().filter(|&c| c == 'e' || c == 'E') {
+            text.push(e);
+            self.next();
+            if let Some(sign) = self.peek().filter(|&c| c == '+' || c == '-') {
+                text.push(sign);
+                self.next();
+            }
+            if self.parse_digits(&mut text) == 0 {
+                return self.error("expected digits in exponent".to_string());
+            }
+        }
+        Ok(Value::Number(text))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.next().and_then(|c| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return self.error("expected 4 hex digits after \\u".to_string()),
+            }
+        }
+        Ok(code)
+    }
+
+    fn parse_string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                None => return self.error("string is never closed".to_string()),
+                Some('"') => return Ok(s),
+                Some('\\') => match self.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => {
+                        let mut code = self.parse_hex4()?;
+                        // Characters outside the Basic Multilingual Plane are written as a surrogate pair
+                        if (0xD800..0xDC00).contains(&code) {
+                            self.expect('\\')?;
+                            self.expect('u')?;
+                            let low = self.parse_hex4()?;
+                            if !(0xDC00..0xE000).contains(&low) {
+                                return self.error(format!("invalid low surrogate \\u{:04x}", low));
+                            }
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                        }
+                        match std::char::from_u32(code) {
+                            Some(c) => s.push(c),
+                            None => return self.error(format!("invalid code point \\u{:04x}", code)),
+                        }
+                    }
+                    Some(c) => return self.error(format!("invalid escape \\{}", c)),
+                    None => return self.error("string is never closed".to_string()),
+                },
+                Some(c) if (c as u32) < 0x20 => {
+                    return self.error(format!("control character {:?} must be escaped", c))
+                }
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Value, ParseError> {
+        let elements = self.parse_elements()?;
+        Ok(Value::Array(elements.into_iter().map(|element| element.value).collect()))
+    }
+
+    fn parse_elements(&mut self) -> Result<Vec<Element>, ParseError> {
+        self.expect('[')?;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(elements);
+        }
+        loop {
+            self.skip_whitespace();
+            let (line, column) = (self.line, self.column + 1);
+            elements.push(Element { line, column, value: self.parse_value()? });
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(elements),
+                Some(c) => return self.error(format!("expected ',' or ']' but found {:?}", c)),
+                None => return self.error("array is never closed".to_string()),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Value, ParseError> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            members.push((key, value));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::Object(members)),
+                Some(c) => return self.error(format!("expected ',' or '}}' but found {:?}", c)),
+                None => return self.error("object is never closed".to_string()),
+            }
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser { chars: input.chars().peekable(), line: 1, column: 0, depth: 0 };
+    let value = parser.parse_value()?;
+    parser.finish(value)
+}
+
+// Input which has to be an array, split into its elements so that callers can tell where each one is
+pub fn parse_elements(input: &str) -> Result<Vec<Element>, ParseError> {
+    let mut parser = Parser { chars: input.chars().peekable(), line: 1, column: 0, depth: 1 };
+    parser.skip_whitespace();
+    match parser.peek() {
+        Some('[') => (),
+        Some(c) => {
+            parser.next();
+            return parser.error(format!("expected an array but found {:?}", c));
+        }
+        None => return parser.error(String::from("expected an array but input ended")),
+    }
+    let elements = parser.parse_elements()?;
+    parser.finish(elements)
+}
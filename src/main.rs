@@ -10,30 +10,255 @@
 // mod variables;
 // mod functions;
 // mod branches;
-// mod slices;
-// mod structs;
-// mod rectangles;
-// mod enums;
-// mod collections;
+// The lesson shows moves and borrows by leaving some values unused
+#[allow(unused_variables, path_statements)]
+mod slices;
+mod structs;
+mod rectangles;
+mod enums;
+// The lesson binds values only to show their types, and spells out matches and vec! on purpose
+#[allow(unused_variables, clippy::manual_unwrap_or, clippy::useless_vec)]
+mod collections;
 // mod errors;
 // mod generics;
 // mod closures;
 // mod iterators;
-mod sm_pointers;
+mod users;
+mod email;
+mod csv;
+mod json;
+mod color;
+mod point;
+mod geometry;
+mod canvas;
+mod packing;
+mod quadtree;
+mod scene;
+mod cidr;
+mod screen;
+mod wire;
+mod console;
+mod options;
+mod tokenizer;
+mod textstats;
+mod inspect;
+mod pig_latin;
+mod spreadsheet;
+mod table;
+mod stats;
+mod width;
+
+// Commands are run as 'rust_learner <command> [args..]' e.g. 'cargo run -- palette #ff0000'
+fn run_command(command: &str, args: &[String]) -> Result<(), String> {
+    match command {
+        "palette" => color::palette(args),
+        "quadtree" => quadtree::benchmark(args),
+        "shapes" => scene::shapes(args),
+        "cidr" => cidr::calculator(args),
+        "console" => console::console(args),
+        "textstats" => textstats::textstats(args),
+        "inspect" => inspect::explore(args),
+        "pig_latin" => pig_latin::filter(args),
+        "table" => table::table(args),
+        "stats" => stats::stats(args),
+        "run" => match args {
+            [lesson] => run_lesson(lesson),
+            _ => Err(String::from("usage: run <lesson>")),
+        },
+        _ => Err(format!("unknown command {:?}", command)),
+    }
+}
+
+// Lessons are run as 'rust_learner run <lesson>' e.g. 'cargo run -- run users'
+// Lessons whose module is commented out above are commented out here as well
+fn run_lesson(lesson: &str) -> Result<(), String> {
+    match lesson {
+        // "guessing_game" => guessing_game::run(),
+        // "variables" => variables::run(),
+        // "functions" => functions::run(),
+        // "branches" => branches::run(),
+        "slices" => slices::run(),
+        "structs" => structs::run(),
+        "rectangles" => rectangles::run(),
+        "enums" => enums::run(),
+        "collections" => collections::run(),
+        // "errors" => errors::run(),
+        // "generics" => generics::run(),
+        // "closures" => closures::run(),
+        // "iterators" => iterators::run(),
+        "users" => users::run(),
+        "email" => email::run(),
+        "color" => color::run(),
+        "point" => point::run(),
+        "geometry" => geometry::run(),
+        "canvas" => canvas::run(),
+        "packing" => packing::run(),
+        "quadtree" => quadtree::run(),
+        "scene" => scene::run(),
+        "cidr" => cidr::run(),
+        "screen" => screen::run(),
+        "wire" => wire::run(),
+        "console" => console::run(),
+        "options" => options::run(),
+        "tokenizer" => tokenizer::run(),
+        "textstats" => textstats::run(),
+        "inspect" => inspect::run(),
+        "pig_latin" => pig_latin::run(),
+        "spreadsheet" => spreadsheet::run(),
+        "table" => table::run(),
+        "stats" => stats::run(),
+        _ => return Err(format!("unknown lesson {:?}", lesson)),
+    }
+    Ok(())
+}
 
 fn main() {
-    // guessing_game::run();
-    // variables::run();
-    // functions::run();
-    // branches::run();
-    // slices::run();
-    // structs::run();
-    // rectangles::run();
-    // enums::run();
-    // collections::run();
-    // errors::run();
-    // generics::run();
-    // closures::run();
-    // iterators::run();
-    sm_pointers::run();
+    let args: Vec<String> = std::env::args().collect();
+    let command = match args.get(1) {
+        Some(command) => command,
+        None => {
+            eprintln!("usage: rust_learner <command> [args..] or rust_learner run <lesson>");
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = run_command(command, &args[2..]) {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+// Every lesson checks its results with assertions, so running it is its test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices() {
+        run_lesson("slices").unwrap();
+    }
+
+    #[test]
+    fn structs() {
+        run_lesson("structs").unwrap();
+    }
+
+    #[test]
+    fn rectangles() {
+        run_lesson("rectangles").unwrap();
+    }
+
+    #[test]
+    fn enums() {
+        run_lesson("enums").unwrap();
+    }
+
+    #[test]
+    fn collections() {
+        run_lesson("collections").unwrap();
+    }
+
+    #[test]
+    fn users() {
+        run_lesson("users").unwrap();
+    }
+
+    #[test]
+    fn email() {
+        run_lesson("email").unwrap();
+    }
+
+    #[test]
+    fn color() {
+        run_lesson("color").unwrap();
+    }
+
+    #[test]
+    fn point() {
+        run_lesson("point").unwrap();
+    }
+
+    #[test]
+    fn geometry() {
+        run_lesson("geometry").unwrap();
+    }
+
+    #[test]
+    fn canvas() {
+        run_lesson("canvas").unwrap();
+    }
+
+    #[test]
+    fn packing() {
+        run_lesson("packing").unwrap();
+    }
+
+    #[test]
+    fn quadtree() {
+        run_lesson("quadtree").unwrap();
+    }
+
+    #[test]
+    fn scene() {
+        run_lesson("scene").unwrap();
+    }
+
+    #[test]
+    fn cidr() {
+        run_lesson("cidr").unwrap();
+    }
+
+    #[test]
+    fn screen() {
+        run_lesson("screen").unwrap();
+    }
+
+    #[test]
+    fn wire() {
+        run_lesson("wire").unwrap();
+    }
+
+    #[test]
+    fn console() {
+        run_lesson("console").unwrap();
+    }
+
+    #[test]
+    fn options() {
+        run_lesson("options").unwrap();
+    }
+
+    #[test]
+    fn tokenizer() {
+        run_lesson("tokenizer").unwrap();
+    }
+
+    #[test]
+    fn textstats() {
+        run_lesson("textstats").unwrap();
+    }
+
+    #[test]
+    fn inspect() {
+        run_lesson("inspect").unwrap();
+    }
+
+    #[test]
+    fn pig_latin() {
+        run_lesson("pig_latin").unwrap();
+    }
+
+    #[test]
+    fn spreadsheet() {
+        run_lesson("spreadsheet").unwrap();
+    }
+
+    #[test]
+    fn table() {
+        run_lesson("table").unwrap();
+    }
+
+    #[test]
+    fn stats() {
+        run_lesson("stats").unwrap();
+    }
 }
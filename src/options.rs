@@ -0,0 +1,170 @@
+// Option combinators and the explicit 'match' each of them stands for
+//
+// Combinators are methods on Option which take a closure and hand back a new Option (or Result),
+// so a chain of them reads top to bottom instead of nesting one 'match' inside another
+
+use std::ops::Add;
+
+// Adds 'delta' to the value if there is one, works for any type implementing Add
+pub fn add<T: Add<Output = T>>(opt: Option<T>, delta: T) -> Option<T> {
+    opt.map(|value| value + delta)
+}
+
+// Sum of both values, None if either of them is missing
+pub fn add_both<T: Add<Output = T>>(a: Option<T>, b: Option<T>) -> Option<T> {
+    a.zip(b).map(|(a, b)| a + b)
+}
+
+// Sum of all values, None as soon as one of them is missing
+// '?' on an Option returns None from the function right away, just like it returns Err for a Result
+pub fn add_all<T: Add<Output = T> + Default + Copy>(values: &[Option<T>]) -> Option<T> {
+    let mut total = T::default();
+    for value in values {
+        total = total + (*value)?;
+    }
+    Some(total)
+}
+
+// Positive whole number in the text, None for anything else
+pub fn parse_positive(text: &str) -> Option<u32> {
+    text.trim().parse().ok().filter(|&n| n > 0)
+}
+
+// Value of "key=value" in a list of settings, which has to be a positive number
+pub fn setting(settings: &[&str], key: &str) -> Result<u32, String> {
+    let value = settings
+        .iter()
+        .find_map(|line| {
+            let (k, v) = line.split_once('=')?;
+            Some(v).filter(|_| k.trim() == key)
+        })
+        .ok_or(format!("{} is not set", key))?;
+    parse_positive(value).ok_or(format!("{} = {:?} is not a positive number", key, value.trim()))
+}
+
+// First character of the first word, e.g. for initials
+pub fn first_letter(text: &str) -> Option<char> {
+    text.split_whitespace().next().and_then(|word| word.chars().next())
+}
+
+// Each row shows a combinator next to the 'match' it replaces, and checks that both give the same result
+// Clippy would suggest the combinators in place of the matches, which are written out on purpose here
+#[allow(clippy::manual_map, clippy::manual_unwrap_or_default)]
+fn table() {
+    let inputs: [Option<i32>; 3] = [Some(4), Some(-3), None];
+    let half = |n: i32| if n % 2 == 0 { Some(n / 2) } else { None };
+
+    println!("{:32} {:10} {:18} match result", "combinator", "input", "combinator result");
+    for &input in inputs.iter() {
+        let rows: Vec<(&str, String, String)> = vec![
+            (
+                "map(|n| n * 10)",
+                format!("{:?}", input.map(|n| n * 10)),
+                format!(
+                    "{:?}",
+                    match input {
+                        Some(n) => Some(n * 10),
+                        None => None,
+                    }
+                ),
+            ),
+            (
+                "and_then(half)",
+                format!("{:?}", input.and_then(half)),
+                format!(
+                    "{:?}",
+                    match input {
+                        Some(n) => half(n),
+                        None => None,
+                    }
+                ),
+            ),
+            (
+                "filter(|&n| n > 0)",
+                format!("{:?}", input.filter(|&n| n > 0)),
+                format!(
+                    "{:?}",
+                    match input {
+                        Some(n) if n > 0 => Some(n),
+                        _ => None,
+                    }
+                ),
+            ),
+            (
+                "zip(Some('x'))",
+                format!("{:?}", input.zip(Some('x'))),
+                format!(
+                    "{:?}",
+                    match (input, Some('x')) {
+                        (Some(n), Some(c)) => Some((n, c)),
+                        _ => None,
+                    }
+                ),
+            ),
+            (
+                "ok_or(\"missing\")",
+                format!("{:?}", input.ok_or("missing")),
+                format!(
+                    "{:?}",
+                    match input {
+                        Some(n) => Ok(n),
+                        None => Err("missing"),
+                    }
+                ),
+            ),
+            (
+                "unwrap_or_default()",
+                format!("{:?}", input.unwrap_or_default()),
+                format!(
+                    "{:?}",
+                    match input {
+                        Some(n) => n,
+                        None => i32::default(),
+                    }
+                ),
+            ),
+            (
+                "add(input, 6)",
+                format!("{:?}", add(input, 6)),
+                format!(
+                    "{:?}",
+                    match input {
+                        Some(n) => Some(n + 6),
+                        None => None,
+                    }
+                ),
+            ),
+        ];
+
+        for (combinator, with_combinator, with_match) in rows {
+            assert_eq!(with_combinator, with_match, "{} on {:?}", combinator, input);
+            println!("{:32} {:10} {:18} {}", combinator, format!("{:?}", input), with_combinator, with_match);
+        }
+    }
+}
+
+pub fn run() {
+    table();
+
+    // Generic add works for every type with Add, not only i32
+    assert_eq!(add(Some(5), 6), Some(11));
+    assert_eq!(add(Some(1.5), 0.25), Some(1.75));
+    assert_eq!(add(None, 6u64), None);
+    assert_eq!(add_both(Some(2), Some(3)), Some(5));
+    assert_eq!(add_both(Some(2), None), None);
+
+    assert_eq!(add_all(&[Some(1), Some(2), Some(3)]), Some(6));
+    assert_eq!(add_all(&[Some(1), None, Some(3)]), None);
+    assert_eq!(add_all::<i32>(&[]), Some(0));
+
+    assert_eq!(parse_positive(" 42 "), Some(42));
+    assert_eq!(parse_positive("0"), None);
+    assert_eq!(parse_positive("-1"), None);
+    assert_eq!(first_letter("  rust learner"), Some('r'));
+    assert_eq!(first_letter("   "), None);
+
+    let settings = ["width = 80", "height=0", "title = lessons"];
+    println!("{:?}", setting(&settings, "width"));
+    println!("{:?}", setting(&settings, "height"));
+    println!("{:?}", setting(&settings, "depth"));
+}
@@ -0,0 +1,320 @@
+// Bin packing: places rectangles into as few fixed-size bins as possible
+// Builds on Rectangle::can_hold to tell whether an item fits into a bin at all
+
+use crate::canvas::render_shapes;
+use crate::geometry::Shape;
+use crate::rectangles::{PositionedRectangle, Rectangle};
+use std::cmp::Reverse;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Heuristic {
+    // Rows of items, each row as tall as the first item put into it
+    Shelf,
+    // Keeps every maximal free rectangle and picks the one leaving the shortest leftover side
+    MaxRects,
+    // Cuts the free space into two disjoint rectangles after every placement
+    Guillotine,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    // Index of the item in the input
+    pub item: usize,
+    pub bin: usize,
+    pub rect: PositionedRectangle,
+    // Item was turned by 90°, so rect has its width and height swapped
+    pub rotated: bool,
+}
+
+#[derive(Debug)]
+pub struct Packing {
+    pub bin_width: u32,
+    pub bin_height: u32,
+    pub placements: Vec<Placement>,
+    pub bins: usize,
+    // Items larger than a bin, which cannot be placed at all
+    pub unplaced: Vec<usize>,
+}
+
+// Areas are u128 because a single u32 x u32 item already needs all of u64, and there can be many
+impl Packing {
+    pub fn used_area(&self) -> u128 {
+        self.placements.iter().map(|p| p.rect.area() as u128).sum()
+    }
+
+    // Area of all used bins not covered by any item
+    pub fn wasted_area(&self) -> u128 {
+        self.bins as u128 * Rectangle::new(self.bin_width, self.bin_height).area() as u128 - self.used_area()
+    }
+
+    pub fn placements_in(&self, bin: usize) -> Vec<&Placement> {
+        self.placements.iter().filter(|p| p.bin == bin).collect()
+    }
+}
+
+// Placements always lie inside a bin, and bins have u32 sides, so they fit into a PositionedRectangle
+fn rect(x: u32, y: u32, width: u32, height: u32) -> PositionedRectangle {
+    PositionedRectangle::new(x, y, width, height).expect("rectangles inside a bin fit in u32")
+}
+
+// Orientations in which an item can be tried, the boolean tells whether it is rotated
+fn orientations(item: &Rectangle, allow_rotation: bool) -> Vec<(u32, u32, bool)> {
+    let mut result = vec![(item.width, item.height, false)];
+    if allow_rotation && item.width != item.height {
+        result.push((item.height, item.width, true));
+    }
+    result
+}
+
+// Free space of one bin, implemented differently for every heuristic
+trait Bin {
+    // Places the item if there is room for it
+    fn insert(&mut self, item: &Rectangle, allow_rotation: bool) -> Option<(PositionedRectangle, bool)>;
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    used_width: u32,
+}
+
+struct ShelfBin {
+    width: u32,
+    height: u32,
+    shelves: Vec<Shelf>,
+    // Top of the space below the last shelf
+    next_y: u32,
+}
+
+impl Bin for ShelfBin {
+    fn insert(&mut self, item: &Rectangle, allow_rotation: bool) -> Option<(PositionedRectangle, bool)> {
+        // First shelf which has room, trying each orientation
+        for shelf in self.shelves.iter_mut() {
+            for (w, h, rotated) in orientations(item, allow_rotation) {
+                if h <= shelf.height && w <= self.width - shelf.used_width {
+                    let placed = rect(shelf.used_width, shelf.y, w, h);
+                    shelf.used_width += w;
+                    return Some((placed, rotated));
+                }
+            }
+        }
+
+        // Otherwise open a new shelf, as low as possible so that less height is wasted
+        let (w, h, rotated) = orientations(item, allow_rotation)
+            .into_iter()
+            .filter(|&(w, h, _)| w <= self.width && h <= self.height - self.next_y)
+            .min_by_key(|&(_, h, _)| h)?;
+        let placed = rect(0, self.next_y, w, h);
+        self.shelves.push(Shelf { y: self.next_y, height: h, used_width: w });
+        self.next_y += h;
+        Some((placed, rotated))
+    }
+}
+
+struct MaxRectsBin {
+    free: Vec<PositionedRectangle>,
+}
+
+impl Bin for MaxRectsBin {
+    fn insert(&mut self, item: &Rectangle, allow_rotation: bool) -> Option<(PositionedRectangle, bool)> {
+        // Best short side fit: the free rectangle leaving the smallest leftover along its shorter side
+        let mut best: Option<((u32, u32), PositionedRectangle, bool)> = None;
+        for free in &self.free {
+            for (w, h, rotated) in orientations(item, allow_rotation) {
+                if w > free.width() || h > free.height() {
+                    continue;
+                }
+                let (dw, dh) = (free.width() - w, free.height() - h);
+                let score = (dw.min(dh), dw.max(dh));
+                if best.as_ref().is_none_or(|(best_score, _, _)| score < *best_score) {
+                    best = Some((score, rect(free.x(), free.y(), w, h), rotated));
+                }
+            }
+        }
+        let (_, placed, rotated) = best?;
+
+        // Every free rectangle overlapping the item is replaced by the maximal rectangles around the item
+        let mut free = Vec::new();
+        for f in &self.free {
+            if !f.overlaps(&placed) {
+                free.push(*f);
+                continue;
+            }
+            let pieces = [
+                rect(f.x(), f.y(), placed.x().saturating_sub(f.x()), f.height()),
+                rect(placed.right(), f.y(), f.right().saturating_sub(placed.right()), f.height()),
+                rect(f.x(), f.y(), f.width(), placed.y().saturating_sub(f.y())),
+                rect(f.x(), placed.bottom(), f.width(), f.bottom().saturating_sub(placed.bottom())),
+            ];
+            free.extend(pieces.iter().filter(|p| !p.is_empty()));
+        }
+
+        // Free rectangles inside other free rectangles are redundant
+        let mut pruned: Vec<PositionedRectangle> = Vec::new();
+        for (i, f) in free.iter().enumerate() {
+            let redundant = free
+                .iter()
+                .enumerate()
+                .any(|(j, other)| i != j && other.contains_rect(f) && (other != f || j < i));
+            if !redundant {
+                pruned.push(*f);
+            }
+        }
+        self.free = pruned;
+        Some((placed, rotated))
+    }
+}
+
+struct GuillotineBin {
+    free: Vec<PositionedRectangle>,
+}
+
+impl Bin for GuillotineBin {
+    fn insert(&mut self, item: &Rectangle, allow_rotation: bool) -> Option<(PositionedRectangle, bool)> {
+        // Best area fit: the smallest free rectangle that can take the item
+        let mut best: Option<(u64, usize, u32, u32, bool)> = None;
+        for (i, free) in self.free.iter().enumerate() {
+            for (w, h, rotated) in orientations(item, allow_rotation) {
+                if w <= free.width() && h <= free.height() && best.is_none_or(|b| free.area() < b.0) {
+                    best = Some((free.area(), i, w, h, rotated));
+                }
+            }
+        }
+        let (_, i, w, h, rotated) = best?;
+        let f = self.free.swap_remove(i);
+        let placed = rect(f.x(), f.y(), w, h);
+
+        // Cut along the shorter leftover axis so that the larger piece stays as big as possible
+        let (right, below) = if f.width() - w < f.height() - h {
+            (rect(placed.right(), f.y(), f.width() - w, h), rect(f.x(), placed.bottom(), f.width(), f.height() - h))
+        } else {
+            (rect(placed.right(), f.y(), f.width() - w, f.height()), rect(f.x(), placed.bottom(), w, f.height() - h))
+        };
+        self.free.extend([right, below].iter().filter(|p| !p.is_empty()));
+        Some((placed, rotated))
+    }
+}
+
+fn new_bin(heuristic: Heuristic, width: u32, height: u32) -> Box<dyn Bin> {
+    match heuristic {
+        Heuristic::Shelf => Box::new(ShelfBin { width, height, shelves: Vec::new(), next_y: 0 }),
+        Heuristic::MaxRects => Box::new(MaxRectsBin { free: vec![rect(0, 0, width, height)] }),
+        Heuristic::Guillotine => Box::new(GuillotineBin { free: vec![rect(0, 0, width, height)] }),
+    }
+}
+
+// Packs the items into bins of the given size, opening a new bin whenever an item fits into none of the open ones
+pub fn pack(items: &[Rectangle], bin_size: &Rectangle, heuristic: Heuristic, allow_rotation: bool) -> Packing {
+    let mut packing = Packing {
+        bin_width: bin_size.width,
+        bin_height: bin_size.height,
+        placements: Vec::new(),
+        bins: 0,
+        unplaced: Vec::new(),
+    };
+
+    // Placing big items first leaves the small ones to fill the gaps
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    // Shelves are filled best when the tallest items come first
+    match (heuristic, allow_rotation) {
+        (Heuristic::Shelf, false) => order.sort_by_key(|&i| Reverse(items[i].height)),
+        (Heuristic::Shelf, true) => order.sort_by_key(|&i| Reverse(items[i].height.max(items[i].width))),
+        _ => order.sort_by_key(|&i| Reverse(items[i].area())),
+    }
+
+    let mut bins: Vec<Box<dyn Bin>> = Vec::new();
+    for i in order {
+        let item = &items[i];
+        let fits_empty_bin = bin_size.can_hold(item)
+            || (allow_rotation && bin_size.can_hold(&Rectangle::new(item.height, item.width)));
+        if !fits_empty_bin {
+            packing.unplaced.push(i);
+            continue;
+        }
+
+        let mut placed = None;
+        for (b, bin) in bins.iter_mut().enumerate() {
+            if let Some((rect, rotated)) = bin.insert(item, allow_rotation) {
+                placed = Some((b, rect, rotated));
+                break;
+            }
+        }
+        let (bin, rect, rotated) = match placed {
+            Some(placed) => placed,
+            None => {
+                let mut bin = new_bin(heuristic, bin_size.width, bin_size.height);
+                let (rect, rotated) = bin.insert(item, allow_rotation).expect("item fits an empty bin");
+                bins.push(bin);
+                (bins.len() - 1, rect, rotated)
+            }
+        };
+        packing.placements.push(Placement { item: i, bin, rect, rotated });
+    }
+
+    packing.bins = bins.len();
+    packing.unplaced.sort_unstable();
+    packing
+}
+
+pub fn run() {
+    let sizes = [(40, 30), (30, 20), (60, 15), (20, 20), (25, 40), (10, 50), (35, 10), (50, 25), (15, 15), (120, 10)];
+    let items: Vec<Rectangle> = sizes.iter().map(|&(w, h)| Rectangle::new(w, h)).collect();
+    let bin = Rectangle::new(80, 60);
+
+    for &heuristic in [Heuristic::Shelf, Heuristic::MaxRects, Heuristic::Guillotine].iter() {
+        for &allow_rotation in [false, true].iter() {
+            let packing = pack(&items, &bin, heuristic, allow_rotation);
+            println!(
+                "{:?} (rotation: {}): {} bins, wasted area {}, unplaced {:?}",
+                heuristic,
+                allow_rotation,
+                packing.bins,
+                packing.wasted_area(),
+                packing.unplaced
+            );
+
+            // Every item is either placed or unplaced, and only once
+            let mut seen = vec![0; items.len()];
+            for &i in packing.placements.iter().map(|p| &p.item).chain(packing.unplaced.iter()) {
+                seen[i] += 1;
+            }
+            assert!(seen.iter().all(|&count| count == 1), "{:?}", seen);
+
+            // Placements have the size of their item, turned when rotated
+            for p in &packing.placements {
+                let item = &items[p.item];
+                let expected = if p.rotated { (item.height, item.width) } else { (item.width, item.height) };
+                assert_eq!((p.rect.width(), p.rect.height()), expected, "{:?}", p);
+                assert!(allow_rotation || !p.rotated);
+            }
+
+            // Placed items never overlap and always stay inside their bin
+            let bin_rect = PositionedRectangle::new(0, 0, bin.width, bin.height).unwrap();
+            for (i, a) in packing.placements.iter().enumerate() {
+                assert!(bin_rect.contains_rect(&a.rect));
+                for b in &packing.placements[i + 1..] {
+                    assert!(a.bin != b.bin || !a.rect.overlaps(&b.rect));
+                }
+            }
+        }
+    }
+
+    // Areas of huge bins don't overflow
+    let huge = || Rectangle::new(u32::MAX, u32::MAX);
+    let packing = pack(&[huge(), huge(), Rectangle::new(u32::MAX, 1)], &huge(), Heuristic::MaxRects, false);
+    assert_eq!(packing.bins, 3);
+    let bin_area = u32::MAX as u128 * u32::MAX as u128;
+    assert_eq!(packing.used_area(), 2 * bin_area + u32::MAX as u128);
+    assert_eq!(packing.wasted_area(), bin_area - u32::MAX as u128);
+
+    // Drawing the first bin of a MaxRects packing
+    let packing = pack(&items, &bin, Heuristic::MaxRects, true);
+    let bin_rect = PositionedRectangle::new(0, 0, bin.width, bin.height).unwrap();
+    let placements = packing.placements_in(0);
+    let labels: Vec<String> = placements.iter().map(|p| p.item.to_string()).collect();
+    let mut shapes: Vec<(&str, &dyn Shape)> = vec![("", &bin_rect)];
+    for (label, placement) in labels.iter().zip(placements.iter()) {
+        shapes.push((label, &placement.rect));
+    }
+    print!("{}", render_shapes(&shapes, 80, 30));
+}
@@ -0,0 +1,170 @@
+// Pig Latin, the practice exercise of the strings chapter
+//
+//   first  -> irst-fay    the consonants before the first vowel move to the end followed by "ay"
+//   string -> ing-stray   the whole consonant cluster moves, "qu" counts as one consonant
+//   apple  -> apple-hay   words starting with a vowel get "hay" instead
+//
+// Capitalisation is kept ("Hello" -> "Ello-hay", "HELLO" -> "ELLO-HAY"), and everything which isn't part
+// of a word (spaces, punctuation, digits) is copied as it is. Words are taken apart by chars rather than bytes,
+// so accented letters are fine and words in non-Latin scripts are left alone. Combining marks stay with the letter
+// before them, so a decomposed "cafe\u{301}" is translated the same as "café".
+
+use crate::inspect::is_extender;
+use std::io::{self, BufRead, Write};
+
+fn is_vowel(c: char) -> bool {
+    c.to_lowercase().any(|c| "aeiouàáâãäåæèéêëìíîïòóôõöøœùúûü".contains(c))
+}
+
+// Latin letters reach up to U+024F, later blocks are other scripts like Greek, Cyrillic or Devanagari
+fn is_latin(c: char) -> bool {
+    c.is_alphabetic() && c <= '\u{24f}'
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '\u{2019}'
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// Translates a single word made of letters and apostrophes
+pub fn translate_word(word: &str) -> String {
+    let first = match word.chars().next() {
+        Some(first) if is_latin(first) => first,
+        _ => return word.to_string(),
+    };
+
+    // Byte offset of the first vowel, 'y' is a vowel unless it starts the word as in "yellow"
+    // Combining marks are part of the letter before them, so the split never separates them from it
+    let mut split = word.len();
+    let mut previous = None;
+    for (i, c) in word.char_indices().filter(|&(_, c)| !is_extender(c)) {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let after_q = lower == 'u' && previous == Some('q');
+        if !after_q && (is_vowel(c) || (lower == 'y' && i > 0)) {
+            split = i;
+            break;
+        }
+        previous = Some(lower);
+    }
+
+    let translated = if split == 0 {
+        format!("{}-hay", word)
+    } else if split == word.len() {
+        // No vowel at all, like "hmm"
+        format!("{}-ay", word)
+    } else {
+        format!("{}-{}ay", &word[split..], &word[..split])
+    };
+
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    let all_caps = letters.len() > 1 && letters.iter().all(|c| c.is_uppercase());
+    if all_caps {
+        translated.to_uppercase()
+    } else if first.is_uppercase() {
+        capitalize(&translated.to_lowercase())
+    } else {
+        translated
+    }
+}
+
+// Translates every word of the text, keeping everything between the words as it is
+// A word is a run of letters and the combining marks following them, with apostrophes allowed between letters as in "don't"
+pub fn translate(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut word_start: Option<usize> = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let next_is_letter = chars.peek().is_some_and(|&(_, next)| next.is_alphabetic());
+        let in_word = c.is_alphabetic()
+            || (is_extender(c) && word_start.is_some())
+            || (is_apostrophe(c) && word_start.is_some() && next_is_letter);
+        match (in_word, word_start) {
+            (true, None) => word_start = Some(i),
+            (false, Some(start)) => {
+                out += &translate_word(&text[start..i]);
+                word_start = None;
+                out.push(c);
+            }
+            (false, None) => out.push(c),
+            (true, Some(_)) => (),
+        }
+    }
+    if let Some(start) = word_start {
+        out += &translate_word(&text[start..]);
+    }
+    out
+}
+
+// 'pig_latin' command: translates stdin line by line onto stdout
+pub fn filter(args: &[String]) -> Result<(), String> {
+    if !args.is_empty() {
+        return Err(String::from("usage: pig_latin < input"));
+    }
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        writeln!(out, "{}", translate(&line)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn run() {
+    let table = [
+        // Single consonants and clusters
+        ("first", "irst-fay"),
+        ("pig", "ig-pay"),
+        ("string", "ing-stray"),
+        ("three", "ee-thray"),
+        ("queen", "een-quay"),
+        ("square", "are-squay"),
+        // Vowels
+        ("apple", "apple-hay"),
+        ("eat", "eat-hay"),
+        ("a", "a-hay"),
+        // 'y' is a consonant at the start and a vowel anywhere else
+        ("yellow", "ellow-yay"),
+        ("rhythm", "ythm-rhay"),
+        ("my", "y-may"),
+        ("hmm", "hmm-ay"),
+        // Capitalisation
+        ("Hello", "Ello-hay"),
+        ("Apple", "Apple-hay"),
+        ("HELLO", "ELLO-HAY"),
+        ("I", "I-hay"),
+        ("Queen", "Een-quay"),
+        // Punctuation and apostrophes
+        ("Hello, world!", "Ello-hay, orld-way!"),
+        ("don't", "on't-day"),
+        ("(it's 'quoted')", "(it's-hay 'oted-quay')"),
+        ("well-known", "ell-way-own-knay"),
+        ("3 pigs", "3 igs-pay"),
+        // Non-ASCII input
+        ("café", "afé-cay"),
+        // Decomposed letters, where the accent is a combining mark after the base letter
+        ("cafe\u{301}", "afe\u{301}-cay"),
+        ("e\u{301}cole", "e\u{301}cole-hay"),
+        ("c\u{327}a va", "a-c\u{327}ay a-vay"),
+        ("qu\u{308}ite", "ite-qu\u{308}ay"),
+        ("École", "École-hay"),
+        ("Über straße", "Über-hay aße-stray"),
+        ("नमस्ते world", "नमस्ते orld-way"),
+        ("Привет", "Привет"),
+        ("  spaces\tand\ttabs  ", "  aces-spay\tand-hay\tabs-tay  "),
+        ("", ""),
+    ];
+    for &(input, expected) in table.iter() {
+        let translated = translate(input);
+        assert_eq!(translated, expected, "translating {:?}", input);
+        println!("{:24} {}", input, translated);
+    }
+}
@@ -0,0 +1,151 @@
+// 3D point/vector tuple struct which is generic over its component type
+// Operators are implemented through the traits in std::ops, e.g. 'a + b' calls Add::add(a, b)
+
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point<T>(pub T, pub T, pub T);
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.0, self.1, self.2)
+    }
+}
+
+// Component-wise addition
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point(self.0 + other.0, self.1 + other.1, self.2 + other.2)
+    }
+}
+
+// Component-wise subtraction
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, other: Point<T>) -> Point<T> {
+        Point(self.0 - other.0, self.1 - other.1, self.2 - other.2)
+    }
+}
+
+// Scaling by a scalar of the same type: 'point * 2'
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, scalar: T) -> Point<T> {
+        Point(self.0 * scalar, self.1 * scalar, self.2 * scalar)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Point<T>;
+
+    fn neg(self) -> Point<T> {
+        Point(-self.0, -self.1, -self.2)
+    }
+}
+
+impl<T> Point<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Copy,
+{
+    pub fn dot(self, other: Point<T>) -> T {
+        self.0 * other.0 + self.1 * other.1 + self.2 * other.2
+    }
+
+    // Vector perpendicular to both self and other
+    pub fn cross(self, other: Point<T>) -> Point<T> {
+        Point(
+            self.1 * other.2 - self.2 * other.1,
+            self.2 * other.0 - self.0 * other.2,
+            self.0 * other.1 - self.1 * other.0,
+        )
+    }
+
+    // Squared length stays in T, so it is exact for integers
+    pub fn length_squared(self) -> T {
+        self.dot(self)
+    }
+}
+
+// Lengths involve a square root and are hence computed in f64 for any type that converts into it
+impl<T: Into<f64> + Copy> Point<T> {
+    pub fn to_f64(self) -> Point<f64> {
+        Point(self.0.into(), self.1.into(), self.2.into())
+    }
+
+    pub fn length(self) -> f64 {
+        self.to_f64().length_squared().sqrt()
+    }
+
+    pub fn distance(self, other: Point<T>) -> f64 {
+        (self.to_f64() - other.to_f64()).length()
+    }
+
+    // Unit vector in the same direction, None for the zero vector which has no direction
+    pub fn normalize(self) -> Option<Point<f64>> {
+        let length = self.length();
+        if length == 0.0 {
+            None
+        } else {
+            Some(self.to_f64() * (1.0 / length))
+        }
+    }
+}
+
+// Identities the operators have to satisfy for any points a, b and scalar k
+fn check_identities<T>(a: Point<T>, b: Point<T>, k: T)
+where
+    T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Neg<Output = T> + Into<f64> + Copy + PartialEq + fmt::Debug,
+{
+    assert_eq!(a + b, b + a);
+    assert_eq!((a - b) + b, a);
+    assert_eq!(-(-a), a);
+    assert_eq!((a + b) * k, a * k + b * k);
+    assert_eq!(a.dot(b), b.dot(a));
+    assert_eq!(a.dot(a), a.length_squared());
+    // length() goes through a square root, so squaring it back is only close
+    let length_squared: f64 = a.dot(a).into();
+    assert!((a.length().powi(2) - length_squared).abs() <= 1e-9 * length_squared.max(1.0));
+}
+
+pub fn run() {
+    // Small integers and floats with exact binary fractions, so that the float results are exact too
+    let ints = [Point(0, 0, 0), Point(1, 2, 3), Point(-4, 5, -6), Point(100, -7, 42)];
+    let floats = [Point(0.0, 0.0, 0.0), Point(0.5, -1.25, 3.0), Point(-2.75, 8.0, 0.125), Point(1e3, -0.5, 64.0)];
+    for i in 0..ints.len() {
+        for j in 0..ints.len() {
+            for &k in [-3, 0, 2].iter() {
+                check_identities(ints[i], ints[j], k);
+            }
+            for &k in [-0.5, 0.0, 4.0].iter() {
+                check_identities(floats[i], floats[j], k);
+            }
+        }
+    }
+
+    let a = Point(1, 2, 3);
+    let b = Point(4, 5, 6);
+
+    // Debug is derived and Display is implemented, so both can be printed
+    println!("a: {:?} b: {}", a, b);
+    println!("a + b = {}", a + b);
+    println!("a - b = {}", a - b);
+    println!("a * 2 = {}", a * 2);
+    println!("-a = {}", -a);
+    println!("a . b = {}", a.dot(b));
+    println!("a x b = {}", a.cross(b));
+
+    // Cross product is perpendicular to both vectors
+    assert_eq!(a.cross(b).dot(a), 0);
+    assert_eq!(a.cross(b).dot(b), 0);
+
+    // Same operations work for floats
+    let p = Point(3.0, 4.0, 0.0);
+    println!("|{}| = {}", p, p.length());
+    println!("normalized: {:?}", p.normalize());
+    println!("distance a to b: {:.3}", a.distance(b));
+}
@@ -0,0 +1,343 @@
+// Quadtree spatial index for PositionedRectangles
+// Every node covers a region and is split into four quadrants once it holds too many rectangles,
+// so queries only look at the rectangles in the regions they touch instead of all of them
+
+use crate::rectangles::PositionedRectangle;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::time::Instant;
+
+// A node is split when it holds more rectangles than this
+const MAX_ITEMS: usize = 8;
+const MAX_DEPTH: usize = 16;
+
+struct Node {
+    bounds: PositionedRectangle,
+    depth: usize,
+    // Rectangles which don't fit entirely into one of the children stay in the node itself
+    items: Vec<(usize, PositionedRectangle)>,
+    children: Vec<Node>,
+}
+
+// Squared distance from a point to the closest cell of a rectangle, 0 when the point is inside
+// An empty rectangle is treated as the single cell at its corner
+// u128 because the squared distance of two u32 coordinates doesn't fit into u64
+fn distance_squared(rect: &PositionedRectangle, x: u32, y: u32) -> u128 {
+    let gap = |value: u32, start: u32, end: u32| {
+        let last = if end > start { end - 1 } else { start };
+        if value < start {
+            (start - value) as u128
+        } else if value > last {
+            (value - last) as u128
+        } else {
+            0
+        }
+    };
+    let dx = gap(x, rect.x(), rect.right());
+    let dy = gap(y, rect.y(), rect.bottom());
+    dx * dx + dy * dy
+}
+
+impl Node {
+    fn new(bounds: PositionedRectangle, depth: usize) -> Node {
+        Node { bounds, depth, items: Vec::new(), children: Vec::new() }
+    }
+
+    fn insert(&mut self, id: usize, rect: PositionedRectangle) {
+        // Empty rectangles can sit on the outer edge of a quadrant, which would throw off the distances
+        // used by nearest(), so they are kept out of the children
+        let child = self.children.iter_mut().find(|child| !rect.is_empty() && child.bounds.contains_rect(&rect));
+        if let Some(child) = child {
+            child.insert(id, rect);
+            return;
+        }
+        self.items.push((id, rect));
+        if self.children.is_empty() && self.items.len() > MAX_ITEMS {
+            self.split();
+        }
+    }
+
+    // Creates the four quadrants and moves down every rectangle which fits into one of them
+    fn split(&mut self) {
+        let b = self.bounds;
+        if self.depth >= MAX_DEPTH || b.width() < 2 || b.height() < 2 {
+            return;
+        }
+        let (left, top) = (b.width() / 2, b.height() / 2);
+        let (right, bottom) = (b.width() - left, b.height() - top);
+        let quadrants = [
+            (b.x(), b.y(), left, top),
+            (b.x() + left, b.y(), right, top),
+            (b.x(), b.y() + top, left, bottom),
+            (b.x() + left, b.y() + top, right, bottom),
+        ];
+        self.children = quadrants
+            .iter()
+            .map(|&(x, y, w, h)| Node::new(PositionedRectangle::new(x, y, w, h).unwrap(), self.depth + 1))
+            .collect();
+
+        for (id, rect) in std::mem::take(&mut self.items) {
+            self.insert(id, rect);
+        }
+    }
+
+    fn remove(&mut self, id: usize, rect: &PositionedRectangle) -> bool {
+        if let Some(i) = self.items.iter().position(|&(item, _)| item == id) {
+            self.items.swap_remove(i);
+            return true;
+        }
+        match self.children.iter_mut().find(|child| !rect.is_empty() && child.bounds.contains_rect(rect)) {
+            Some(child) => child.remove(id, rect),
+            None => false,
+        }
+    }
+
+    // Calls 'visit' for every rectangle in the nodes for which 'enter' returns true
+    fn visit<E, V>(&self, enter: &E, visit: &mut V)
+    where
+        E: Fn(&PositionedRectangle) -> bool,
+        V: FnMut(usize, &PositionedRectangle),
+    {
+        for (id, rect) in &self.items {
+            visit(*id, rect);
+        }
+        for child in self.children.iter().filter(|child| enter(&child.bounds)) {
+            child.visit(enter, visit);
+        }
+    }
+
+    // Updates 'best' with the closest rectangle in this node, skipping nodes which cannot be closer
+    fn nearest(&self, x: u32, y: u32, best: &mut Option<(u128, usize)>) {
+        for (id, rect) in &self.items {
+            let candidate = (distance_squared(rect, x, y), *id);
+            if best.is_none_or(|b| candidate < b) {
+                *best = Some(candidate);
+            }
+        }
+
+        // Closest quadrants first, as they are the most likely to improve 'best'
+        let mut children: Vec<(u128, &Node)> =
+            self.children.iter().map(|child| (distance_squared(&child.bounds, x, y), child)).collect();
+        children.sort_by_key(|&(distance, _)| distance);
+        for (distance, child) in children {
+            if best.is_none_or(|(best_distance, _)| distance <= best_distance) {
+                child.nearest(x, y, best);
+            }
+        }
+    }
+}
+
+pub struct QuadTree {
+    root: Node,
+    // Rectangles by id, so that remove() knows where to look
+    rects: HashMap<usize, PositionedRectangle>,
+}
+
+impl QuadTree {
+    // Rectangles outside 'bounds' can still be inserted, they are kept in the root node
+    pub fn new(bounds: PositionedRectangle) -> QuadTree {
+        QuadTree { root: Node::new(bounds, 0), rects: HashMap::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn get(&self, id: usize) -> Option<&PositionedRectangle> {
+        self.rects.get(&id)
+    }
+
+    // Inserting an id which is already present replaces its rectangle
+    pub fn insert(&mut self, id: usize, rect: PositionedRectangle) {
+        self.remove(id);
+        self.root.insert(id, rect);
+        self.rects.insert(id, rect);
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<PositionedRectangle> {
+        let rect = self.rects.remove(&id)?;
+        self.root.remove(id, &rect);
+        Some(rect)
+    }
+
+    // Ids of the rectangles overlapping 'area', sorted
+    pub fn query(&self, area: &PositionedRectangle) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.root.visit(&|bounds: &PositionedRectangle| bounds.overlaps(area), &mut |id, rect| {
+            if rect.overlaps(area) {
+                found.push(id);
+            }
+        });
+        found.sort_unstable();
+        found
+    }
+
+    // Ids of the rectangles containing the point, sorted
+    pub fn query_point(&self, x: u32, y: u32) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.root.visit(&|bounds: &PositionedRectangle| bounds.contains_point(x, y), &mut |id, rect| {
+            if rect.contains_point(x, y) {
+                found.push(id);
+            }
+        });
+        found.sort_unstable();
+        found
+    }
+
+    // Id of the rectangle closest to the point, the lowest id wins a tie
+    pub fn nearest(&self, x: u32, y: u32) -> Option<usize> {
+        let mut best = None;
+        self.root.nearest(x, y, &mut best);
+        best.map(|(_, id)| id)
+    }
+
+    // Every pair of overlapping rectangles as (smaller id, larger id), sorted
+    pub fn collisions(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for (&id, rect) in &self.rects {
+            pairs.extend(self.query(rect).into_iter().filter(|&other| other > id).map(|other| (id, other)));
+        }
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+// Brute force versions of the queries, which check every (id, rectangle) entry
+// Entries have to be sorted by id, so that the results come out sorted like the quadtree's
+fn brute_query(rects: &[(usize, PositionedRectangle)], area: &PositionedRectangle) -> Vec<usize> {
+    rects.iter().filter(|(_, rect)| rect.overlaps(area)).map(|&(id, _)| id).collect()
+}
+
+fn brute_query_point(rects: &[(usize, PositionedRectangle)], x: u32, y: u32) -> Vec<usize> {
+    rects.iter().filter(|(_, rect)| rect.contains_point(x, y)).map(|&(id, _)| id).collect()
+}
+
+fn brute_nearest(rects: &[(usize, PositionedRectangle)], x: u32, y: u32) -> Option<usize> {
+    rects.iter().min_by_key(|&&(id, rect)| (distance_squared(&rect, x, y), id)).map(|&(id, _)| id)
+}
+
+fn brute_collisions(rects: &[(usize, PositionedRectangle)]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, (a, rect)) in rects.iter().enumerate() {
+        for (b, other) in &rects[i + 1..] {
+            if rect.overlaps(other) {
+                pairs.push((*a, *b));
+            }
+        }
+    }
+    pairs
+}
+
+fn random_rect(rng: &mut impl Rng, world: u32, max_side: u32) -> PositionedRectangle {
+    let (w, h) = (rng.gen_range(1, max_side), rng.gen_range(1, max_side));
+    PositionedRectangle::new(rng.gen_range(0, world - w), rng.gen_range(0, world - h), w, h).unwrap()
+}
+
+// 'quadtree' command: times the quadtree against brute force on random rectangles
+// and checks that both always give the same answers
+pub fn benchmark(args: &[String]) -> Result<(), String> {
+    let count: usize = match args.first() {
+        Some(arg) => arg.parse().map_err(|_| format!("{:?} is not a number of rectangles", arg))?,
+        None => 5000,
+    };
+    let queries = 1000;
+    let world = 100_000;
+
+    let mut rng = rand::thread_rng();
+    let rects: Vec<(usize, PositionedRectangle)> = (0..count).map(|id| (id, random_rect(&mut rng, world, 500))).collect();
+    let areas: Vec<PositionedRectangle> = (0..queries).map(|_| random_rect(&mut rng, world, 5000)).collect();
+    let points: Vec<(u32, u32)> = (0..queries).map(|_| (rng.gen_range(0, world), rng.gen_range(0, world))).collect();
+
+    let start = Instant::now();
+    let mut tree = QuadTree::new(PositionedRectangle::new(0, 0, world, world).unwrap());
+    for &(id, rect) in &rects {
+        tree.insert(id, rect);
+    }
+    println!("Building a quadtree of {} rectangles: {:?}", count, start.elapsed());
+
+    let start = Instant::now();
+    let tree_results: Vec<Vec<usize>> = areas.iter().map(|area| tree.query(area)).collect();
+    let tree_time = start.elapsed();
+    let start = Instant::now();
+    let brute_results: Vec<Vec<usize>> = areas.iter().map(|area| brute_query(&rects, area)).collect();
+    println!("{} range queries: quadtree {:?}, brute force {:?}", queries, tree_time, start.elapsed());
+    assert_eq!(tree_results, brute_results);
+
+    let start = Instant::now();
+    let tree_results: Vec<Option<usize>> = points.iter().map(|&(x, y)| tree.nearest(x, y)).collect();
+    let tree_time = start.elapsed();
+    let start = Instant::now();
+    let brute_results: Vec<Option<usize>> = points.iter().map(|&(x, y)| brute_nearest(&rects, x, y)).collect();
+    println!("{} nearest neighbours: quadtree {:?}, brute force {:?}", queries, tree_time, start.elapsed());
+    assert_eq!(tree_results, brute_results);
+
+    let start = Instant::now();
+    let tree_pairs = tree.collisions();
+    let tree_time = start.elapsed();
+    let start = Instant::now();
+    let brute_pairs = brute_collisions(&rects);
+    println!("{} colliding pairs: quadtree {:?}, brute force {:?}", tree_pairs.len(), tree_time, start.elapsed());
+    assert_eq!(tree_pairs, brute_pairs);
+
+    Ok(())
+}
+
+pub fn run() {
+    let mut tree = QuadTree::new(PositionedRectangle::new(0, 0, 100, 100).unwrap());
+    tree.insert(0, PositionedRectangle::new(10, 10, 20, 20).unwrap());
+    tree.insert(1, PositionedRectangle::new(25, 25, 10, 10).unwrap());
+    tree.insert(2, PositionedRectangle::new(70, 70, 5, 5).unwrap());
+    tree.insert(3, PositionedRectangle::new(60, 10, 30, 5).unwrap());
+
+    println!("Overlapping (0, 0, 50, 50): {:?}", tree.query(&PositionedRectangle::new(0, 0, 50, 50).unwrap()));
+    println!("Containing (27, 27): {:?}", tree.query_point(27, 27));
+    println!("Nearest to (90, 90): {:?}", tree.nearest(90, 90));
+    println!("Collisions: {:?}", tree.collisions());
+
+    tree.remove(1);
+    println!("Collisions after removing 1: {:?}", tree.collisions());
+
+    // Same answers as a linear scan on seeded random data, before and after removing every third rectangle
+    // Some rectangles lie outside the tree's bounds, which keeps them in the root node
+    let mut rng = StdRng::seed_from_u64(36);
+    let world = 1000;
+    let mut rects: Vec<(usize, PositionedRectangle)> = (0..300).map(|id| (id, random_rect(&mut rng, world, 60))).collect();
+    let mut tree = QuadTree::new(PositionedRectangle::new(0, 0, world / 2, world / 2).unwrap());
+    for &(id, rect) in &rects {
+        tree.insert(id, rect);
+    }
+    let areas: Vec<PositionedRectangle> = (0..100).map(|_| random_rect(&mut rng, world, 200)).collect();
+    let points: Vec<(u32, u32)> = (0..100).map(|_| (rng.gen_range(0, world), rng.gen_range(0, world))).collect();
+
+    for round in 0..2 {
+        assert_eq!(tree.len(), rects.len());
+        for (id, rect) in &rects {
+            assert_eq!(tree.get(*id), Some(rect), "round {} id {}", round, id);
+        }
+        for area in &areas {
+            assert_eq!(tree.query(area), brute_query(&rects, area), "round {} area {:?}", round, area);
+        }
+        for &(x, y) in &points {
+            assert_eq!(tree.query_point(x, y), brute_query_point(&rects, x, y), "round {} point ({}, {})", round, x, y);
+            assert_eq!(tree.nearest(x, y), brute_nearest(&rects, x, y), "round {} point ({}, {})", round, x, y);
+        }
+        assert_eq!(tree.collisions(), brute_collisions(&rects), "round {}", round);
+
+        for &(id, rect) in rects.iter().filter(|(id, _)| id % 3 == 0) {
+            assert_eq!(tree.remove(id), Some(rect));
+        }
+        rects.retain(|(id, _)| id % 3 != 0);
+    }
+    assert_eq!(tree.remove(0), None);
+    assert_eq!(tree.get(0), None);
+    for &(id, _) in &rects {
+        tree.remove(id);
+    }
+    assert!(tree.is_empty() && tree.query(&PositionedRectangle::new(0, 0, world, world).unwrap()).is_empty());
+    println!("Quadtree agrees with a linear scan before and after removals");
+}
@@ -1,11 +1,13 @@
 // Rectangles is an example to using and implementing Structs
 
+use crate::canvas::render_shapes;
+use crate::geometry::{BoundingBox, Shape};
 
 // Deriving Debug Trait for printing debug output for Rectangle struct
 #[derive(Debug)]
-struct Rectangle{
-    width: u32,
-    height: u32,
+pub struct Rectangle{
+    pub width: u32,
+    pub height: u32,
 }
 
 pub fn run(){
@@ -19,6 +21,8 @@ pub fn run(){
     println!("Area of rect2: {}", rect2.area());
 
     println!("Can rect1 hold rect2 ? {}", rect1.can_hold(&rect2));
+    // Drawing both rectangles from the same corner shows whether one fits inside the other
+    print!("{}", render_shapes(&[("rect1", &rect1), ("rect2", &rect2)], 60, 20));
 
     rect1.drop();
     // rect1 is invalidated since it was moved into .drop() method and not returned
@@ -27,11 +31,88 @@ pub fn run(){
     // Associated functions let you namespace functionality particular to you struct with instantiation
     println!("A square: {:?}", Rectangle::square(20));
 
+    // Areas of huge rectangles don't overflow
+    let huge = Rectangle::square(u32::MAX);
+    assert_eq!(huge.area(), 18_446_744_065_119_617_025);
+    assert_eq!(huge.checked_area(), None);
+    assert_eq!(Rectangle::new(65_536, 65_535).checked_area(), Some(u32::MAX - 65_535));
+    assert_eq!(Rectangle::new(65_536, 65_536).checked_area(), None);
+    println!("Area of the largest square: {}", area_func(&huge));
+
+
+    // Rectangles with a position can answer questions that depend on where they are
+    let a = PositionedRectangle::new(0, 0, 30, 60).unwrap();
+    let b = PositionedRectangle::new(20, 40, 30, 40).unwrap();
+    // Rectangles reaching past u32::MAX are rejected instead of having edges that overflow
+    assert_eq!(PositionedRectangle::new(u32::MAX - 10, 0, 11, 1), None);
+    let corner = PositionedRectangle::new(u32::MAX - 10, u32::MAX - 10, 10, 10).unwrap();
+    assert_eq!(corner.union(&a).area(), huge.area());
+    println!("a: {:?}\nb: {:?}", a, b);
+    println!("Can a hold b ignoring position? {}", a.size().can_hold(&b.size()));
+    println!("Does a contain b? {}", a.contains_rect(&b));
+    println!("Do a and b overlap? {}", a.overlaps(&b));
+    println!("Intersection: {:?}", a.intersection(&b));
+    println!("Union: {:?}", a.union(&b));
+    println!("a without b: {:?}", a.subtract(&b));
+    assert_eq!((b.x(), b.y(), b.width(), b.height()), (20, 40, 30, 40));
+    assert!(!a.contains_rect(&b) && a.overlaps(&b));
+    assert_eq!(a.intersection(&b), PositionedRectangle::new(20, 40, 10, 20));
+    assert_eq!(Some(a.union(&b)), PositionedRectangle::new(0, 0, 50, 80));
+    let pieces = vec![PositionedRectangle::new(0, 0, 30, 40).unwrap(), PositionedRectangle::new(0, 40, 20, 20).unwrap()];
+    assert_eq!(a.subtract(&b), pieces);
+    // Points on any edge are part of the rectangle, whether given as integers or through Shape
+    assert!(a.contains_point(0, 0) && a.contains_point(29, 59) && a.contains_point(30, 60));
+    assert!(!a.contains_point(31, 0) && !a.contains_point(0, 61));
+    let square = PositionedRectangle::new(0, 0, 10, 10).unwrap();
+    for &(x, y) in [(0, 0), (10, 5), (5, 10), (10, 10), (11, 5), (5, 11)].iter() {
+        assert_eq!(square.contains_point(x, y), Shape::contains(&square, x as f64, y as f64), "({}, {})", x, y);
+    }
+    assert!(PositionedRectangle::new(u32::MAX, 0, 1, 1).is_none());
+    print!("{}", render_shapes(&[("a", &a), ("b", &b)], 60, 20));
+
+    // Properties checked for every pair of rectangles from a grid of positions and sizes, empty ones included
+    let mut grid = Vec::new();
+    for &x in [0, 3, 7].iter() {
+        for &y in [0, 3, 7].iter() {
+            for &width in [0, 2, 5, 9].iter() {
+                for &height in [0, 2, 5, 9].iter() {
+                    grid.push(PositionedRectangle::new(x, y, width, height).unwrap());
+                }
+            }
+        }
+    }
+    for a in &grid {
+        for b in &grid {
+            check_properties(a, b);
+        }
+    }
+    println!("Checked intersection, union and subtract for {} pairs", grid.len() * grid.len());
+
+}
+
+fn check_properties(a: &PositionedRectangle, b: &PositionedRectangle) {
+    let overlap = a.intersection(b).map_or(0, |overlap| overlap.area());
+    assert!(overlap <= a.area().min(b.area()), "{:?} and {:?}", a, b);
+
+    let union = a.union(b);
+    assert!(union.contains_rect(a) && union.contains_rect(b), "{:?} and {:?}", a, b);
+
+    // The pieces cover exactly the part of a which isn't covered by b
+    let pieces = a.subtract(b);
+    for (i, piece) in pieces.iter().enumerate() {
+        assert!(a.contains_rect(piece) && !piece.overlaps(b), "{:?} - {:?}: {:?}", a, b, piece);
+        for other in &pieces[i + 1..] {
+            assert!(!piece.overlaps(other), "{:?} - {:?}: {:?} and {:?}", a, b, piece, other);
+        }
+    }
+    let total: u64 = pieces.iter().map(|piece| piece.area()).sum();
+    assert_eq!(total, a.area() - overlap, "{:?} - {:?}", a, b);
 }
 
 // Function to compute area of a Rectangle instance
-fn area_func(rect: &Rectangle) -> u32 {
-    rect.width * rect.height
+// Sides are widened to u64 before multiplying, since u32 * u32 can overflow u32 but never u64
+fn area_func(rect: &Rectangle) -> u64 {
+    rect.width as u64 * rect.height as u64
 }
 
 // Implementation Block
@@ -39,12 +120,17 @@ impl Rectangle{
 
     // First parameter of a method is always self (take ownership/borrow/borrow mutably)
     // Here, we have borrowed self immutably
-    fn area(&self) -> u32 {
-        self.width * self.height
+    pub fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    // Area as u32, None when it doesn't fit instead of panicking (debug) or wrapping around (release)
+    pub fn checked_area(&self) -> Option<u32> {
+        self.width.checked_mul(self.height)
     }
 
     // Method with multiple parameters
-    fn can_hold(&self, other_rect: &Rectangle) -> bool {
+    pub fn can_hold(&self, other_rect: &Rectangle) -> bool {
         (self.height >= other_rect.height) && (self.width >= other_rect.width)
     }
 
@@ -59,7 +145,182 @@ impl Rectangle{
 
     // Associated functions are similar to Static methods in other langauages 
     // These do not take self as a parameter
-    fn square(size: u32) -> Rectangle {
+    pub fn square(size: u32) -> Rectangle {
         Rectangle{height: size, width: size}
     }
-}
\ No newline at end of file
+
+    pub fn new(width: u32, height: u32) -> Rectangle {
+        Rectangle{width, height}
+    }
+}
+
+// Traits can be implemented in addition to the Implementation Blocks
+// A Rectangle has no position, so as a Shape it has its top-left corner at the origin
+impl Shape for Rectangle {
+    fn name(&self) -> &'static str {
+        "rectangle"
+    }
+
+    // Calls the inherent area() method, as inherent methods take precedence over trait methods
+    fn area(&self) -> f64 {
+        Rectangle::area(self) as f64
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width as f64 + self.height as f64)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox{ min_x: 0.0, min_y: 0.0, max_x: self.width as f64, max_y: self.height as f64 }
+    }
+
+    fn contains(&self, x: f64, y: f64) -> bool {
+        self.bounding_box().contains(x, y)
+    }
+}
+
+// Rectangle placed on a grid with its top-left corner at (x, y), y grows downwards
+// Fields are private to keep the whole rectangle inside the u32 coordinate space, i.e. x + width <= u32::MAX
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionedRectangle{
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl PositionedRectangle{
+
+    // None when the rectangle would extend past u32::MAX
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Option<PositionedRectangle> {
+        x.checked_add(width)?;
+        y.checked_add(height)?;
+        Some(PositionedRectangle{x, y, width, height})
+    }
+
+    // Rectangle spanning from (left, top) up to but not including (right, bottom)
+    fn from_edges(left: u32, top: u32, right: u32, bottom: u32) -> PositionedRectangle {
+        PositionedRectangle{x: left, y: top, width: right - left, height: bottom - top}
+    }
+
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Right and bottom edges cannot overflow because of the check in new()
+    pub fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    // u64 fits the area of any rectangle made of u32 sides
+    pub fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    // Same size without the position
+    pub fn size(&self) -> Rectangle {
+        Rectangle::new(self.width, self.height)
+    }
+
+    // Like Rectangle::can_hold but taking positions into account
+    pub fn contains_rect(&self, other: &PositionedRectangle) -> bool {
+        other.x >= self.x && other.y >= self.y && other.right() <= self.right() && other.bottom() <= self.bottom()
+    }
+
+    // Points on the edges are inside, like for Shape::contains
+    pub fn contains_point(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x <= self.right() && y >= self.y && y <= self.bottom()
+    }
+
+    // Rectangles which only touch along an edge do not overlap
+    pub fn overlaps(&self, other: &PositionedRectangle) -> bool {
+        self.x < other.right() && other.x < self.right() && self.y < other.bottom() && other.y < self.bottom()
+    }
+
+    // Overlapping part of both rectangles, None when they don't overlap
+    pub fn intersection(&self, other: &PositionedRectangle) -> Option<PositionedRectangle> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        Some(PositionedRectangle::from_edges(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.right().min(other.right()),
+            self.bottom().min(other.bottom()),
+        ))
+    }
+
+    // Smallest rectangle containing both rectangles
+    pub fn union(&self, other: &PositionedRectangle) -> PositionedRectangle {
+        PositionedRectangle::from_edges(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.right().max(other.right()),
+            self.bottom().max(other.bottom()),
+        )
+    }
+
+    // Parts of self not covered by other as up to four non-overlapping rectangles:
+    // full-width strips above and below the overlap, and the pieces left and right of it
+    pub fn subtract(&self, other: &PositionedRectangle) -> Vec<PositionedRectangle> {
+        let overlap = match self.intersection(other) {
+            Some(overlap) => overlap,
+            None => return vec![*self],
+        };
+
+        let pieces = vec![
+            PositionedRectangle::from_edges(self.x, self.y, self.right(), overlap.y),
+            PositionedRectangle::from_edges(self.x, overlap.bottom(), self.right(), self.bottom()),
+            PositionedRectangle::from_edges(self.x, overlap.y, overlap.x, overlap.bottom()),
+            PositionedRectangle::from_edges(overlap.right(), overlap.y, self.right(), overlap.bottom()),
+        ];
+        pieces.into_iter().filter(|piece| !piece.is_empty()).collect()
+    }
+}
+
+impl Shape for PositionedRectangle {
+    fn name(&self) -> &'static str {
+        "rectangle"
+    }
+
+    fn area(&self) -> f64 {
+        PositionedRectangle::area(self) as f64
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width as f64 + self.height as f64)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox{
+            min_x: self.x as f64,
+            min_y: self.y as f64,
+            max_x: self.right() as f64,
+            max_y: self.bottom() as f64,
+        }
+    }
+
+    fn contains(&self, x: f64, y: f64) -> bool {
+        self.bounding_box().contains(x, y)
+    }
+}
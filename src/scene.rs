@@ -0,0 +1,268 @@
+// Text format for describing a scene of named shapes, one shape per line:
+//
+//   # comments and empty lines are ignored
+//   rect     name x y width height
+//   square   name x y size
+//   circle   name x y radius
+//   triangle name x1 y1 x2 y2 x3 y3
+//   polygon  name x1 y1 x2 y2 x3 y3 [x4 y4 ...]
+//
+// Rectangles are PositionedRectangles and hence take whole non-negative numbers,
+// all other shapes take any decimal numbers
+
+use crate::canvas::render_shapes;
+use crate::geometry::{Circle, Polygon, Shape, Square, Triangle};
+use crate::rectangles::PositionedRectangle;
+use std::fmt;
+use std::fs;
+
+#[derive(Debug)]
+pub struct NamedShape {
+    pub name: String,
+    pub shape: Box<dyn Shape>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ErrorKind {
+    UnknownShape(String),
+    MissingName,
+    // Shape kind along with a description of the numbers it takes and how many were found
+    ArgumentCount { shape: String, expected: &'static str, found: usize },
+    InvalidNumber(String),
+    NegativeSize(String),
+    // Rectangle reaching past u32::MAX
+    TooLarge(String),
+    DuplicateName(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ErrorKind::UnknownShape(kind) => {
+                write!(f, "unknown shape {:?}, expected rect, square, circle, triangle or polygon", kind)
+            }
+            ErrorKind::MissingName => write!(f, "shape has no name"),
+            ErrorKind::ArgumentCount { shape, expected, found } => {
+                write!(f, "{} takes {} but {} numbers were given", shape, expected, found)
+            }
+            ErrorKind::InvalidNumber(arg) => write!(f, "{:?} is not a valid number", arg),
+            ErrorKind::NegativeSize(arg) => write!(f, "size {} cannot be negative", arg),
+            ErrorKind::TooLarge(name) => write!(f, "rectangle {:?} is too large", name),
+            ErrorKind::DuplicateName(name) => write!(f, "there is already a shape named {:?}", name),
+        }
+    }
+}
+
+// Parses every argument as a number of type T, e.g. u32 for rectangles and f64 for everything else
+fn numbers<T: std::str::FromStr>(args: &[&str]) -> Result<Vec<T>, ErrorKind> {
+    args.iter()
+        .map(|arg| arg.parse().map_err(|_| ErrorKind::InvalidNumber(arg.to_string())))
+        .collect()
+}
+
+// Parses every argument as f64, "inf" and "NaN" parse too but are no use as coordinates or sizes
+fn coordinates(args: &[&str]) -> Result<Vec<f64>, ErrorKind> {
+    let n: Vec<f64> = numbers(args)?;
+    match n.iter().position(|value| !value.is_finite()) {
+        Some(i) => Err(ErrorKind::InvalidNumber(args[i].to_string())),
+        None => Ok(n),
+    }
+}
+
+fn non_negative(value: f64, arg: &str) -> Result<f64, ErrorKind> {
+    if value < 0.0 {
+        Err(ErrorKind::NegativeSize(arg.to_string()))
+    } else {
+        Ok(value)
+    }
+}
+
+fn parse_shape(kind: &str, name: &str, args: &[&str]) -> Result<Box<dyn Shape>, ErrorKind> {
+    let count_error = |expected| ErrorKind::ArgumentCount { shape: kind.to_string(), expected, found: args.len() };
+
+    match kind {
+        "rect" => {
+            if args.len() != 4 {
+                return Err(count_error("4 numbers: x y width height"));
+            }
+            let n: Vec<u32> = numbers(args)?;
+            match PositionedRectangle::new(n[0], n[1], n[2], n[3]) {
+                Some(rect) => Ok(Box::new(rect)),
+                None => Err(ErrorKind::TooLarge(name.to_string())),
+            }
+        }
+        "square" => {
+            if args.len() != 3 {
+                return Err(count_error("3 numbers: x y size"));
+            }
+            let n = coordinates(args)?;
+            Ok(Box::new(Square { x: n[0], y: n[1], side: non_negative(n[2], args[2])? }))
+        }
+        "circle" => {
+            if args.len() != 3 {
+                return Err(count_error("3 numbers: x y radius"));
+            }
+            let n = coordinates(args)?;
+            Ok(Box::new(Circle { center: (n[0], n[1]), radius: non_negative(n[2], args[2])? }))
+        }
+        "triangle" => {
+            if args.len() != 6 {
+                return Err(count_error("6 numbers: x1 y1 x2 y2 x3 y3"));
+            }
+            let n = coordinates(args)?;
+            Ok(Box::new(Triangle { a: (n[0], n[1]), b: (n[2], n[3]), c: (n[4], n[5]) }))
+        }
+        "polygon" => {
+            if args.len() < 6 || !args.len().is_multiple_of(2) {
+                return Err(count_error("an even number of at least 6 numbers: x1 y1 x2 y2 x3 y3 ..."));
+            }
+            let n = coordinates(args)?;
+            let points = n.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+            Ok(Box::new(Polygon::new(points).expect("at least 3 points were checked above")))
+        }
+        _ => Err(ErrorKind::UnknownShape(kind.to_string())),
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<NamedShape>, ParseError> {
+    let mut shapes: Vec<NamedShape> = Vec::new();
+
+    for (i, text) in input.lines().enumerate() {
+        let line = i + 1;
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let (kind, name, args) = match words.as_slice() {
+            [kind, name, args @ ..] => (*kind, *name, args),
+            _ => return Err(ParseError { line, kind: ErrorKind::MissingName }),
+        };
+        if shapes.iter().any(|shape| shape.name == name) {
+            return Err(ParseError { line, kind: ErrorKind::DuplicateName(name.to_string()) });
+        }
+
+        let shape = parse_shape(kind, name, args).map_err(|kind| ParseError { line, kind })?;
+        shapes.push(NamedShape { name: name.to_string(), shape });
+    }
+    Ok(shapes)
+}
+
+// Pairs (outer, inner) of shapes where the inner shape lies entirely inside the outer one
+pub fn containments(shapes: &[NamedShape]) -> Vec<(&str, &str)> {
+    let mut pairs = Vec::new();
+    for outer in shapes {
+        for inner in shapes {
+            if outer.name != inner.name && outer.shape.contains_shape(inner.shape.as_ref()) {
+                pairs.push((outer.name.as_str(), inner.name.as_str()));
+            }
+        }
+    }
+    pairs
+}
+
+// 'shapes' command: loads a scene file, prints the areas and containments and draws the scene
+pub fn shapes(args: &[String]) -> Result<(), String> {
+    let path = match args {
+        [path] => path,
+        _ => return Err(String::from("usage: shapes <scene file>")),
+    };
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let shapes = parse(&text).map_err(|e| format!("{}: {}", path, e))?;
+
+    for named in &shapes {
+        println!(
+            "{:12} {:9} area: {:10.2} perimeter: {:8.2}",
+            named.name,
+            named.shape.name(),
+            named.shape.area(),
+            named.shape.perimeter()
+        );
+    }
+    let total: f64 = shapes.iter().map(|named| named.shape.area()).sum();
+    println!("Total area: {:.2}", total);
+
+    println!();
+    for (outer, inner) in containments(&shapes) {
+        println!("{} contains {}", outer, inner);
+    }
+
+    println!();
+    let labelled: Vec<(&str, &dyn Shape)> = shapes.iter().map(|named| (named.name.as_str(), named.shape.as_ref())).collect();
+    print!("{}", render_shapes(&labelled, 80, 40));
+    Ok(())
+}
+
+pub fn run() {
+    let text = "\
+# A house on a lawn
+rect lawn 0 0 80 40
+rect house 10 8 30 24
+triangle roof 10 8 25 0 40 8
+square window 14 14 6
+circle sun 65 10 6
+polygon pond 50 25 70 25 74 34 54 36
+";
+    let shapes = parse(text).unwrap();
+    let parsed: Vec<String> = shapes.iter().map(|named| format!("{} {:?}", named.name, named.shape)).collect();
+    assert_eq!(
+        parsed,
+        [
+            "lawn PositionedRectangle { x: 0, y: 0, width: 80, height: 40 }",
+            "house PositionedRectangle { x: 10, y: 8, width: 30, height: 24 }",
+            "roof Triangle { a: (10.0, 8.0), b: (25.0, 0.0), c: (40.0, 8.0) }",
+            "window Square { x: 14.0, y: 14.0, side: 6.0 }",
+            "sun Circle { center: (65.0, 10.0), radius: 6.0 }",
+            "pond Polygon { points: [(50.0, 25.0), (70.0, 25.0), (74.0, 34.0), (54.0, 36.0)] }",
+        ]
+    );
+    let pairs = containments(&shapes);
+    for (outer, inner) in &pairs {
+        println!("{} contains {}", outer, inner);
+    }
+    let expected = [
+        ("lawn", "house"),
+        ("lawn", "roof"),
+        ("lawn", "window"),
+        ("lawn", "sun"),
+        ("lawn", "pond"),
+        ("house", "window"),
+    ];
+    assert_eq!(pairs, expected);
+
+    // A bar across the gap of a U has all its corners inside the U but isn't contained in it
+    let text = "polygon u 0 0 30 0 30 30 20 30 20 10 10 10 10 30 0 30\nrect bar 5 15 20 5\nsquare post 2 12 5";
+    let shapes = parse(text).unwrap();
+    assert_eq!(containments(&shapes), [("u", "post")]);
+
+    // Errors tell the line where they happened
+    let errors = [
+        ("rect a 0 0 10 10\ncircle b 1 2", 2, "circle takes 3 numbers: x y radius but 2 numbers were given"),
+        ("rect a 0 0 10 -10", 1, "\"-10\" is not a valid number"),
+        ("hexagon a 0 0", 1, "unknown shape \"hexagon\", expected rect, square, circle, triangle or polygon"),
+        ("# shapes\n\nsquare", 3, "shape has no name"),
+        ("circle c 0 0 1\nsquare c 0 0 1", 2, "there is already a shape named \"c\""),
+        ("circle c 0 0 -1", 1, "size -1 cannot be negative"),
+        ("rect r 4294967295 0 1 1", 1, "rectangle \"r\" is too large"),
+    ];
+    for (input, line, message) in errors.iter() {
+        let error = parse(input).unwrap_err();
+        println!("{}", error);
+        assert_eq!(error.line, *line, "{:?}", input);
+        assert_eq!(error.to_string(), format!("line {}: {}", line, message));
+    }
+
+    for line in ["square s 0 0 inf", "circle c 0 0 nan", "triangle t 0 0 -inf 1 1 1"].iter() {
+        let error = parse(line).unwrap_err();
+        assert!(matches!(error.kind, ErrorKind::InvalidNumber(_)), "{}", error);
+    }
+    assert_eq!(parse("circle c 0 0 NaN").unwrap_err().kind, ErrorKind::InvalidNumber(String::from("NaN")));
+}
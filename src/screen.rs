@@ -0,0 +1,155 @@
+// Screen model driven by enums::Message
+// Every message changes one part of the state: Move shifts the cursor, Write appends text,
+// ChangeColor sets the colour and Quit stops the screen from taking any more messages
+
+use crate::color::Color;
+use crate::enums::Message;
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum ScreenError {
+    // Message arrived after Quit
+    Stopped,
+    // Moving the cursor by (x, y) would leave the i32 range
+    CursorOutOfRange { x: i32, y: i32 },
+    // ChangeColor channels have to be between 0 and 255
+    ColorOutOfRange(i32, i32, i32),
+}
+
+impl fmt::Display for ScreenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScreenError::Stopped => write!(f, "the screen has quit"),
+            ScreenError::CursorOutOfRange { x, y } => write!(f, "cannot move the cursor by ({}, {})", x, y),
+            ScreenError::ColorOutOfRange(r, g, b) => {
+                write!(f, "colour ({}, {}, {}) has channels outside 0 to 255", r, g, b)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screen {
+    cursor: (i32, i32),
+    text: String,
+    color: Color,
+    running: bool,
+}
+
+impl Default for Screen {
+    fn default() -> Screen {
+        Screen::new()
+    }
+}
+
+impl Screen {
+    // Cursor at the origin, no text, white and running
+    pub fn new() -> Screen {
+        Screen { cursor: (0, 0), text: String::new(), color: Color(255, 255, 255), running: true }
+    }
+
+    pub fn cursor(&self) -> (i32, i32) {
+        self.cursor
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    // Applies a single message, the state is left unchanged when it fails
+    pub fn apply(&mut self, message: &Message) -> Result<(), ScreenError> {
+        if !self.running {
+            return Err(ScreenError::Stopped);
+        }
+
+        match message {
+            Message::Quit => self.running = false,
+            // Moves are relative to the current cursor
+            Message::Move { x, y } => {
+                let (cx, cy) = self.cursor;
+                match (cx.checked_add(*x), cy.checked_add(*y)) {
+                    (Some(nx), Some(ny)) => self.cursor = (nx, ny),
+                    _ => return Err(ScreenError::CursorOutOfRange { x: *x, y: *y }),
+                }
+            }
+            Message::Write(text) => self.text.push_str(text),
+            Message::ChangeColor(r, g, b) => {
+                let channel = |value: i32| u8::try_from(value).ok();
+                match (channel(*r), channel(*g), channel(*b)) {
+                    (Some(r), Some(g), Some(b)) => self.color = Color(r, g, b),
+                    _ => return Err(ScreenError::ColorOutOfRange(*r, *g, *b)),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Applies messages until Quit or the first error, and returns how many were applied including the Quit
+    // Messages after Quit are not taken from the iterator
+    pub fn dispatch<I>(&mut self, messages: I) -> Result<usize, ScreenError>
+    where
+        I: IntoIterator<Item = Message>,
+    {
+        let mut applied = 0;
+        for message in messages {
+            self.apply(&message)?;
+            applied += 1;
+            if !self.running {
+                break;
+            }
+        }
+        Ok(applied)
+    }
+}
+
+impl fmt::Display for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = if self.running { "running" } else { "stopped" };
+        write!(f, "[{}] cursor ({}, {}) colour {} text {:?}", state, self.cursor.0, self.cursor.1, self.color, self.text)
+    }
+}
+
+pub fn run() {
+    let messages = vec![
+        Message::Write(String::from("Hello")),
+        Message::Move { x: 3, y: 4 },
+        Message::ChangeColor(255, 0, 0),
+        Message::Write(String::from(", world")),
+        Message::Move { x: -1, y: 0 },
+        Message::Quit,
+        Message::Write(String::from("never written")),
+    ];
+
+    let mut screen = Screen::new();
+    let mut remaining = messages.into_iter();
+    let applied = screen.dispatch(remaining.by_ref()).unwrap();
+    println!("Applied {} messages: {}", applied, screen);
+    let remaining: Vec<Message> = remaining.collect();
+    println!("Left over: {:?}", remaining);
+    assert_eq!(screen.cursor(), (2, 4));
+    assert_eq!(screen.text(), "Hello, world");
+    assert_eq!(applied, 6);
+    assert!(!screen.is_running());
+    assert_eq!(remaining, [Message::Write(String::from("never written"))]);
+
+    // Nothing is applied once the screen has quit
+    let stopped = screen.apply(&Message::Move { x: 1, y: 1 }).unwrap_err();
+    println!("{}", stopped);
+    assert_eq!(stopped, ScreenError::Stopped);
+
+    // A failing message stops the dispatch and leaves the state as it was before that message
+    let mut screen = Screen::new();
+    let result = screen.dispatch(vec![Message::ChangeColor(0, 128, 0), Message::ChangeColor(0, 300, 0)]);
+    println!("{} ({})", result.as_ref().unwrap_err(), screen);
+    assert_eq!(result.unwrap_err(), ScreenError::ColorOutOfRange(0, 300, 0));
+    assert_eq!(screen.color(), Color(0, 128, 0));
+}
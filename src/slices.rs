@@ -1,3 +1,4 @@
+use crate::tokenizer::first_word;
 use std::any::type_name;
 
 // Can be ignored for now
@@ -23,13 +24,13 @@ pub fn run(){
     println!("s2: {}", s2);
 
     // String objects cannot be automatically casted to String Slices
-    // println!("s1 first word: {}", get_first_word(s1)); => Also note that this will cause s1 to lose ownership and go out of scope - will cause problems if s1 or s2 is used later
+    // println!("s1 first word: {:?}", first_word(s1)); => Also note that this will cause s1 to lose ownership and go out of scope - will cause problems if s1 or s2 is used later
     // However, String object references CAN be automatically casted to String Slices
-    println!("s1 first word: {}", get_first_word(&s1));
+    // first_word returns a slice of s1, see tokenizer.rs
+    println!("s1 first word: {:?}", first_word(&s1));
 
-    // String slices and their references can be automatically casted to String Slices
-    println!("s2 first word: {}", get_first_word(s2));
-    println!("s2 first word: {}", get_first_word(&s2));
+    // String slices are passed as they are
+    println!("s2 first word: {:?}", first_word(s2));
     
     // Memory locations os string object and string slice
     println!("String object Address: {:?}", s1.as_ptr());
@@ -66,14 +67,3 @@ pub fn run(){
     println!("Type of Array slice: {}", type_of( array_slice ));
 
 }
-
-fn get_first_word(s: &str) -> &str {
-    let byte_str = s.as_bytes();
-    
-    for (i,&item) in byte_str.iter().enumerate(){
-        if item == b' ' {
-            return &s[..i];
-        }
-    }
-    &s[..]
-}
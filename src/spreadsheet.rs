@@ -0,0 +1,789 @@
+// Spreadsheet of SpreadsheetCells with formulas
+//
+// Cells are addressed like "B3" (column B, row 3). A cell holds either a plain value or a formula starting
+// with '=', such as "=A1+B2*2" or "=SUM(A1:A10)". Formulas support + - * /, parentheses, numbers, cell
+// references and the functions SUM, AVERAGE, MIN, MAX and COUNT over values and ranges.
+//
+// Every formula knows the cells it reads, so changing a cell recalculates exactly the cells depending on it,
+// each after the cells it depends on. Cells which depend on themselves are marked as cycles.
+//
+// Grids of plain cells are read from and written to CSV, guessing the type of every field with parse_value().
+
+use crate::collections::vectors::SpreadsheetCell;
+use crate::csv;
+use crate::width::{display_width, pad};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+// Address of a cell, 0-based internally but written like "A1" with rows starting at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CellRef {
+    pub row: usize,
+    pub column: usize,
+}
+
+// Column letters like spreadsheets use them: A..Z, then AA, AB, ...
+fn column_name(mut column: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (column % 26) as u8);
+        if column < 26 {
+            break;
+        }
+        column = column / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+impl CellRef {
+    pub fn new(column: usize, row: usize) -> CellRef {
+        CellRef { row, column }
+    }
+
+    // Parses "B3" (or "b3"), None for anything else
+    pub fn parse(s: &str) -> Option<CellRef> {
+        let letters = s.bytes().take_while(|b| b.is_ascii_alphabetic()).count();
+        let (column, row) = s.split_at(letters);
+        if letters == 0 || row.is_empty() || !row.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut index: usize = 0;
+        for b in column.bytes() {
+            let digit = (b.to_ascii_uppercase() - b'A') as usize + 1;
+            index = index.checked_mul(26)?.checked_add(digit)?;
+        }
+        let row: usize = row.parse().ok()?;
+        if row == 0 {
+            return None;
+        }
+        Some(CellRef { row: row - 1, column: index - 1 })
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", column_name(self.column), self.row + 1)
+    }
+}
+
+impl fmt::Display for SpreadsheetCell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpreadsheetCell::Int(n) => write!(f, "{}", n),
+            SpreadsheetCell::Float(x) => write!(f, "{}", x),
+            SpreadsheetCell::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+// Value typed into a cell: a whole number becomes Int, other numbers Float and everything else Text
+pub fn parse_value(input: &str) -> SpreadsheetCell {
+    let trimmed = input.trim();
+    if let Ok(n) = trimmed.parse() {
+        SpreadsheetCell::Int(n)
+    } else if let Some(x) = trimmed.parse::<f64>().ok().filter(|x| x.is_finite()) {
+        SpreadsheetCell::Float(x)
+    } else {
+        SpreadsheetCell::Text(input.to_string())
+    }
+}
+
+// Rows of the CSV with the type of each field guessed by parse_value()
+pub fn read_csv(input: &str, delimiter: char) -> Result<Vec<Vec<SpreadsheetCell>>, csv::ParseError> {
+    let records = csv::parse_with(input, delimiter)?;
+    Ok(records.into_iter().map(|record| record.fields.iter().map(|field| parse_value(field)).collect()).collect())
+}
+
+// Floats are written with Debug so that 2.0 stays "2.0" and is read back as a Float rather than an Int
+// CSV has no types though, so Text which looks like a number is read back as a number
+pub fn write_csv(rows: &[Vec<SpreadsheetCell>], delimiter: char) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|cell| match cell {
+                SpreadsheetCell::Float(x) => format!("{:?}", x),
+                cell => cell.to_string(),
+            })
+            .collect();
+        let fields: Vec<&str> = fields.iter().map(|field| field.as_str()).collect();
+        out += &csv::write_record_with(&fields, delimiter);
+    }
+    out
+}
+
+// Error values shown in place of a result, like in other spreadsheets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellError {
+    DivisionByZero,
+    // Reference to a cell outside the sheet
+    Reference,
+    // Text where a number was needed
+    Value,
+    // Result too large to be a number
+    Number,
+    // Cell depends on itself, directly or through other cells
+    Cycle,
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let code = match self {
+            CellError::DivisionByZero => "#DIV/0!",
+            CellError::Reference => "#REF!",
+            CellError::Value => "#VALUE!",
+            CellError::Number => "#NUM!",
+            CellError::Cycle => "#CYCLE!",
+        };
+        write!(f, "{}", code)
+    }
+}
+
+pub type CellValue = Result<SpreadsheetCell, CellError>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Sum,
+    Average,
+    Min,
+    Max,
+    Count,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Ref(CellRef),
+    // Only allowed as a function argument
+    Range(CellRef, CellRef),
+    Negate(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+// Cells from the top-left to the bottom-right corner of a range, row by row
+// Only the part within the first 'columns' and 'rows' is enumerated, so "A1:ZZZ2000000" costs no more than the sheet
+fn range_cells(a: CellRef, b: CellRef, columns: usize, rows: usize) -> impl Iterator<Item = CellRef> {
+    let (top, bottom) = (a.row.min(b.row), a.row.max(b.row).saturating_add(1).min(rows));
+    let (left, right) = (a.column.min(b.column), a.column.max(b.column).saturating_add(1).min(columns));
+    (top..bottom).flat_map(move |row| (left..right).map(move |column| CellRef { row, column }))
+}
+
+impl Expr {
+    // Every cell the expression reads, ranges included as far as they lie within 'columns' and 'rows'
+    fn references(&self, columns: usize, rows: usize, found: &mut Vec<CellRef>) {
+        match self {
+            Expr::Number(_) => (),
+            Expr::Ref(cell) => found.push(*cell),
+            Expr::Range(a, b) => found.extend(range_cells(*a, *b, columns, rows)),
+            Expr::Negate(inner) => inner.references(columns, rows, found),
+            Expr::Binary(_, left, right) => {
+                left.references(columns, rows, found);
+                right.references(columns, rows, found);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|arg| arg.references(columns, rows, found)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FormulaError {
+    // Char offset within the formula, after the '='
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at {}: {}", self.position, self.message)
+    }
+}
+
+// Recursive descent parser for the formula after the '='
+//   expr   = term (('+' | '-') term)*
+//   term   = factor (('*' | '/') factor)*
+//   factor = '-' factor | number | cell | function '(' args ')' | '(' expr ')'
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    // Depth of the expression being parsed: every '(', function call and '-' adds a level, and so does every
+    // '+' '-' '*' '/' since "1+2+3" is parsed as (1+2)+3
+    depth: usize,
+}
+
+// Formulas nested deeper than this are rejected instead of running out of stack, when parsing or evaluating them
+pub const MAX_DEPTH: usize = 256;
+
+impl Parser {
+    fn error<T>(&self, message: String) -> Result<T, FormulaError> {
+        Err(FormulaError { position: self.position, message })
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.get(self.position).is_some_and(|c| c.is_whitespace()) {
+            self.position += 1;
+        }
+        self.chars.get(self.position).copied()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), FormulaError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(c) => self.error(format!("expected {:?} but found {:?}", expected, c)),
+            None => self.error(format!("expected {:?} but the formula ended", expected)),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), FormulaError> {
+        if self.depth == MAX_DEPTH {
+            return self.error(format!("formulas cannot be nested more than {} deep", MAX_DEPTH));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn take_while(&mut self, keep: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.chars.get(self.position).is_some_and(|&c| keep(c)) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, FormulaError> {
+        let depth = self.depth;
+        let mut expr = self.parse_term()?;
+        while let Some(op) = self.peek().filter(|&c| c == '+' || c == '-') {
+            self.enter()?;
+            self.position += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.parse_term()?));
+        }
+        self.depth = depth;
+        Ok(expr)
+    }
+
+    fn parse_term(&mut self) -> Result<Expr, FormulaError> {
+        let depth = self.depth;
+        let mut expr = self.parse_factor()?;
+        while let Some(op) = self.peek().filter(|&c| c == '*' || c == '/') {
+            self.enter()?;
+            self.position += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.parse_factor()?));
+        }
+        self.depth = depth;
+        Ok(expr)
+    }
+
+    fn parse_factor(&mut self) -> Result<Expr, FormulaError> {
+        self.enter()?;
+        let factor = self.parse_nested_factor();
+        self.depth -= 1;
+        factor
+    }
+
+    fn parse_nested_factor(&mut self) -> Result<Expr, FormulaError> {
+        match self.peek() {
+            Some('-') => {
+                self.position += 1;
+                Ok(Expr::Negate(Box::new(self.parse_factor()?)))
+            }
+            Some('(') => {
+                self.position += 1;
+                let expr = self.parse_expr()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let start = self.position;
+                let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                match number.parse() {
+                    Ok(x) => Ok(Expr::Number(x)),
+                    Err(_) => Err(FormulaError { position: start, message: format!("{:?} is not a number", number) }),
+                }
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let start = self.position;
+                let name = self.take_while(|c| c.is_ascii_alphanumeric());
+                if self.peek() == Some('(') {
+                    return self.parse_call(&name, start);
+                }
+                match CellRef::parse(&name) {
+                    Some(cell) => Ok(Expr::Ref(cell)),
+                    None => Err(FormulaError { position: start, message: format!("{:?} is not a cell", name) }),
+                }
+            }
+            Some(c) => self.error(format!("unexpected {:?}", c)),
+            None => self.error(String::from("the formula ended too early")),
+        }
+    }
+
+    fn parse_call(&mut self, name: &str, start: usize) -> Result<Expr, FormulaError> {
+        let function = match name.to_ascii_uppercase().as_str() {
+            "SUM" => Function::Sum,
+            "AVERAGE" => Function::Average,
+            "MIN" => Function::Min,
+            "MAX" => Function::Max,
+            "COUNT" => Function::Count,
+            _ => return Err(FormulaError { position: start, message: format!("unknown function {:?}", name) }),
+        };
+        self.expect('(')?;
+
+        let mut args = Vec::new();
+        if self.peek() != Some(')') {
+            loop {
+                let arg = self.parse_expr()?;
+                // "A1:B2" is a range when both sides are cells
+                let arg = match (arg, self.peek()) {
+                    (Expr::Ref(from), Some(':')) => {
+                        self.position += 1;
+                        match self.parse_factor()? {
+                            Expr::Ref(to) => Expr::Range(from, to),
+                            _ => return self.error(String::from("a range has to end with a cell")),
+                        }
+                    }
+                    (arg, _) => arg,
+                };
+                args.push(arg);
+                if self.peek() != Some(',') {
+                    break;
+                }
+                self.position += 1;
+            }
+        }
+        self.expect(')')?;
+        Ok(Expr::Call(function, args))
+    }
+}
+
+// Parses a formula without its leading '='
+pub fn parse_formula(formula: &str) -> Result<Expr, FormulaError> {
+    let mut parser = Parser { chars: formula.chars().collect(), position: 0, depth: 0 };
+    let expr = parser.parse_expr()?;
+    match parser.peek() {
+        Some(c) => parser.error(format!("unexpected {:?}", c)),
+        None => Ok(expr),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SheetError {
+    InvalidAddress(String),
+    OutOfRange(CellRef),
+    Formula { cell: CellRef, error: FormulaError },
+}
+
+impl fmt::Display for SheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SheetError::InvalidAddress(address) => write!(f, "{:?} is not a cell address", address),
+            SheetError::OutOfRange(cell) => write!(f, "{} is outside the sheet", cell),
+            SheetError::Formula { cell, error } => write!(f, "formula in {} {}", cell, error),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Content {
+    Value(SpreadsheetCell),
+    // The formula text is kept so that the cell can be shown and saved as it was typed
+    Formula { source: String, expr: Expr },
+}
+
+fn to_number(value: &CellValue) -> Result<f64, CellError> {
+    match value {
+        Ok(SpreadsheetCell::Int(n)) => Ok(*n as f64),
+        Ok(SpreadsheetCell::Float(x)) => Ok(*x),
+        Ok(SpreadsheetCell::Text(_)) => Err(CellError::Value),
+        Err(e) => Err(*e),
+    }
+}
+
+// Whole results which fit into i32 stay Int, so "=A1+1" of an Int cell is an Int too
+fn from_number(x: f64) -> CellValue {
+    if !x.is_finite() {
+        Err(CellError::Number)
+    } else if x.fract() == 0.0 && x >= i32::MIN as f64 && x <= i32::MAX as f64 {
+        Ok(SpreadsheetCell::Int(x as i32))
+    } else {
+        Ok(SpreadsheetCell::Float(x))
+    }
+}
+
+pub struct Spreadsheet {
+    columns: usize,
+    rows: usize,
+    contents: HashMap<CellRef, Content>,
+    values: HashMap<CellRef, CellValue>,
+    // Cells whose formulas read the key cell
+    dependents: HashMap<CellRef, HashSet<CellRef>>,
+}
+
+impl Spreadsheet {
+    pub fn new(columns: usize, rows: usize) -> Spreadsheet {
+        Spreadsheet { columns, rows, contents: HashMap::new(), values: HashMap::new(), dependents: HashMap::new() }
+    }
+
+    fn in_sheet(&self, cell: CellRef) -> bool {
+        cell.row < self.rows && cell.column < self.columns
+    }
+
+    pub fn cell(&self, address: &str) -> Result<CellRef, SheetError> {
+        let cell = CellRef::parse(address).ok_or_else(|| SheetError::InvalidAddress(address.to_string()))?;
+        if !self.in_sheet(cell) {
+            return Err(SheetError::OutOfRange(cell));
+        }
+        Ok(cell)
+    }
+
+    // Computed value of a cell, None for an empty cell
+    pub fn value(&self, cell: CellRef) -> Option<&CellValue> {
+        self.values.get(&cell)
+    }
+
+    // What was typed into the cell, formulas with their '='
+    pub fn input(&self, cell: CellRef) -> Option<String> {
+        match self.contents.get(&cell)? {
+            Content::Value(value) => Some(value.to_string()),
+            Content::Formula { source, .. } => Some(source.clone()),
+        }
+    }
+
+    // Cells the content reads which lie within the sheet
+    fn precedents(&self, content: Option<&Content>) -> Vec<CellRef> {
+        let mut found = Vec::new();
+        if let Some(Content::Formula { expr, .. }) = content {
+            expr.references(self.columns, self.rows, &mut found);
+        }
+        found.retain(|&cell| self.in_sheet(cell));
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    // Sets a cell from typed text: "=..." is a formula, an empty text clears the cell, anything else is a value
+    pub fn set(&mut self, address: &str, input: &str) -> Result<(), SheetError> {
+        let cell = self.cell(address)?;
+        let content = if let Some(formula) = input.trim_start().strip_prefix('=') {
+            let expr = parse_formula(formula).map_err(|error| SheetError::Formula { cell, error })?;
+            Some(Content::Formula { source: input.trim().to_string(), expr })
+        } else if input.trim().is_empty() {
+            None
+        } else {
+            Some(Content::Value(parse_value(input)))
+        };
+        self.replace(cell, content);
+        Ok(())
+    }
+
+    pub fn set_value(&mut self, address: &str, value: SpreadsheetCell) -> Result<(), SheetError> {
+        let cell = self.cell(address)?;
+        self.replace(cell, Some(Content::Value(value)));
+        Ok(())
+    }
+
+    fn replace(&mut self, cell: CellRef, content: Option<Content>) {
+        for precedent in self.precedents(self.contents.get(&cell)) {
+            if let Some(dependents) = self.dependents.get_mut(&precedent) {
+                dependents.remove(&cell);
+            }
+        }
+        for precedent in self.precedents(content.as_ref()) {
+            self.dependents.entry(precedent).or_default().insert(cell);
+        }
+        match content {
+            Some(content) => self.contents.insert(cell, content),
+            None => self.contents.remove(&cell),
+        };
+        self.recalculate(cell);
+    }
+
+    // The changed cell and every cell depending on it, in an order where each cell comes after the cells it reads
+    // Cells left over could not be ordered, they are part of a cycle or depend on one
+    pub fn recalculation_order(&self, changed: CellRef) -> (Vec<CellRef>, Vec<CellRef>) {
+        let mut affected = HashSet::new();
+        let mut queue = VecDeque::new();
+        affected.insert(changed);
+        queue.push_back(changed);
+        while let Some(cell) = queue.pop_front() {
+            for &dependent in self.dependents.get(&cell).into_iter().flatten() {
+                if affected.insert(dependent) {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+
+        // Kahn's algorithm on the affected cells: a cell is ready once all the affected cells it reads are done
+        let mut waiting: HashMap<CellRef, usize> = affected
+            .iter()
+            .map(|&cell| {
+                let precedents = self.precedents(self.contents.get(&cell));
+                (cell, precedents.iter().filter(|p| affected.contains(p)).count())
+            })
+            .collect();
+        let mut ready: Vec<CellRef> = waiting.iter().filter(|&(_, &count)| count == 0).map(|(&cell, _)| cell).collect();
+        ready.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut order = Vec::new();
+        while let Some(cell) = ready.pop() {
+            order.push(cell);
+            waiting.remove(&cell);
+            for &dependent in self.dependents.get(&cell).into_iter().flatten() {
+                if let Some(count) = waiting.get_mut(&dependent) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(dependent);
+                    }
+                }
+            }
+        }
+
+        let mut cyclic: Vec<CellRef> = waiting.into_keys().collect();
+        cyclic.sort_unstable();
+        (order, cyclic)
+    }
+
+    fn recalculate(&mut self, changed: CellRef) {
+        let (order, cyclic) = self.recalculation_order(changed);
+        for cell in order {
+            let value = match self.contents.get(&cell) {
+                None => None,
+                Some(Content::Value(value)) => Some(Ok(value.clone())),
+                Some(Content::Formula { expr, .. }) => Some(self.evaluate(expr)),
+            };
+            match value {
+                Some(value) => self.values.insert(cell, value),
+                None => self.values.remove(&cell),
+            };
+        }
+        for cell in cyclic {
+            self.values.insert(cell, Err(CellError::Cycle));
+        }
+    }
+
+    // Value of a referenced cell, empty cells count as 0
+    fn lookup(&self, cell: CellRef) -> CellValue {
+        if !self.in_sheet(cell) {
+            return Err(CellError::Reference);
+        }
+        self.values.get(&cell).cloned().unwrap_or(Ok(SpreadsheetCell::Int(0)))
+    }
+
+    fn evaluate(&self, expr: &Expr) -> CellValue {
+        match expr {
+            Expr::Number(x) => from_number(*x),
+            Expr::Ref(cell) => self.lookup(*cell),
+            // Parser only allows ranges inside functions
+            Expr::Range(..) => Err(CellError::Value),
+            Expr::Negate(inner) => from_number(-to_number(&self.evaluate(inner))?),
+            Expr::Binary(op, left, right) => {
+                let left = to_number(&self.evaluate(left))?;
+                let right = to_number(&self.evaluate(right))?;
+                match op {
+                    '+' => from_number(left + right),
+                    '-' => from_number(left - right),
+                    '*' => from_number(left * right),
+                    _ if right == 0.0 => Err(CellError::DivisionByZero),
+                    _ => from_number(left / right),
+                }
+            }
+            Expr::Call(function, args) => self.call(*function, args),
+        }
+    }
+
+    // Numbers of the arguments, text and empty cells inside ranges are skipped like other spreadsheets do
+    fn numbers(&self, args: &[Expr]) -> Result<Vec<f64>, CellError> {
+        let mut numbers = Vec::new();
+        for arg in args {
+            match arg {
+                // A range reaching outside the sheet is an error, found from its corners without going through it
+                Expr::Range(a, b) => {
+                    if !self.in_sheet(*a) || !self.in_sheet(*b) {
+                        return Err(CellError::Reference);
+                    }
+                    for cell in range_cells(*a, *b, self.columns, self.rows) {
+                        match self.values.get(&cell) {
+                            Some(Ok(SpreadsheetCell::Text(_))) | None => (),
+                            Some(value) => numbers.push(to_number(value)?),
+                        }
+                    }
+                }
+                arg => numbers.push(to_number(&self.evaluate(arg))?),
+            }
+        }
+        Ok(numbers)
+    }
+
+    fn call(&self, function: Function, args: &[Expr]) -> CellValue {
+        let numbers = self.numbers(args)?;
+        match function {
+            Function::Sum => from_number(numbers.iter().sum()),
+            Function::Average if numbers.is_empty() => Err(CellError::DivisionByZero),
+            Function::Average => from_number(numbers.iter().sum::<f64>() / numbers.len() as f64),
+            // MIN and MAX of nothing are 0
+            Function::Min => from_number(numbers.iter().copied().reduce(f64::min).unwrap_or(0.0)),
+            Function::Max => from_number(numbers.iter().copied().reduce(f64::max).unwrap_or(0.0)),
+            Function::Count => from_number(numbers.len() as f64),
+        }
+    }
+
+    // Text shown for a cell: its value or error code, "" when empty
+    pub fn display(&self, cell: CellRef) -> String {
+        match self.values.get(&cell) {
+            Some(Ok(value)) => value.to_string(),
+            Some(Err(e)) => e.to_string(),
+            None => String::new(),
+        }
+    }
+
+    // Grid of the computed values with column letters and row numbers, up to the last non-empty row and column
+    pub fn render(&self) -> String {
+        let used_rows = self.values.keys().map(|cell| cell.row + 1).max().unwrap_or(0);
+        let used_columns = self.values.keys().map(|cell| cell.column + 1).max().unwrap_or(0);
+
+        let mut widths: Vec<usize> = (0..used_columns).map(|column| column_name(column).len()).collect();
+        for (cell, _) in self.values.iter() {
+            widths[cell.column] = widths[cell.column].max(display_width(&self.display(*cell)));
+        }
+        let row_label_width = used_rows.to_string().len();
+
+        let mut out = format!("{:w$}", "", w = row_label_width);
+        for (column, width) in widths.iter().enumerate() {
+            out += &format!(" | {:^w$}", column_name(column), w = width);
+        }
+        out = out.trim_end().to_string();
+        out.push('\n');
+        for row in 0..used_rows {
+            out += &format!("{:>w$}", row + 1, w = row_label_width);
+            for (column, width) in widths.iter().enumerate() {
+                let cell = CellRef { row, column };
+                // Numbers are right-aligned, text left-aligned
+                let right = !matches!(self.values.get(&cell), Some(Ok(SpreadsheetCell::Text(_))));
+                out += &format!(" | {}", pad(&self.display(cell), *width, right));
+            }
+            out = out.trim_end().to_string();
+            out.push('\n');
+        }
+        out
+    }
+}
+
+pub fn run() {
+    assert_eq!(CellRef::parse("B3"), Some(CellRef::new(1, 2)));
+    assert_eq!(CellRef::parse("aa10"), Some(CellRef::new(26, 9)));
+    assert_eq!(CellRef::parse("A0"), None);
+    assert_eq!(CellRef::new(701, 0).to_string(), "ZZ1");
+    assert_eq!(CellRef::new(702, 0).to_string(), "AAA1");
+
+    let mut sheet = Spreadsheet::new(26, 100);
+    let inputs = [
+        ("A1", "Item"),
+        ("B1", "Price"),
+        ("C1", "Count"),
+        ("D1", "Total"),
+        ("A2", "Apples"),
+        ("B2", "0.5"),
+        ("C2", "12"),
+        ("D2", "=B2*C2"),
+        ("A3", "Pears"),
+        ("B3", "0.75"),
+        ("C3", "4"),
+        ("D3", "=B3*C3"),
+        ("A4", "Sum"),
+        ("C4", "=SUM(C2:C3)"),
+        ("D4", "=SUM(D2:D3)"),
+        ("A5", "Average"),
+        ("D5", "=AVERAGE(D2:D3)"),
+    ];
+    for (address, input) in inputs.iter() {
+        sheet.set(address, input).unwrap();
+    }
+    print!("{}", sheet.render());
+    let d4 = sheet.cell("D4").unwrap();
+    assert_eq!(sheet.value(d4), Some(&Ok(SpreadsheetCell::Int(9))));
+
+    // Changing a price recalculates its total and then the sums depending on it
+    let (order, _) = sheet.recalculation_order(sheet.cell("B2").unwrap());
+    let order: Vec<String> = order.iter().map(|cell| cell.to_string()).collect();
+    println!("Changing B2 recalculates {:?}", order);
+    sheet.set("B2", "1").unwrap();
+    assert_eq!(sheet.value(d4), Some(&Ok(SpreadsheetCell::Int(15))));
+
+    // Errors spread to every cell reading them
+    sheet.set("E1", "=A2*2").unwrap();
+    sheet.set("E2", "=1/(C3-4)").unwrap();
+    sheet.set("E3", "=E2+1").unwrap();
+    sheet.set("E4", "=Z1000").unwrap();
+    sheet.set("F1", "=G1+1").unwrap();
+    sheet.set("G1", "=H1").unwrap();
+    sheet.set("H1", "=F1").unwrap();
+    for address in ["E1", "E2", "E3", "E4", "F1", "G1", "H1"].iter() {
+        println!("{} {} = {}", address, sheet.input(sheet.cell(address).unwrap()).unwrap(), sheet.display(sheet.cell(address).unwrap()));
+    }
+    assert_eq!(sheet.value(sheet.cell("E3").unwrap()), Some(&Err(CellError::DivisionByZero)));
+    assert_eq!(sheet.value(sheet.cell("G1").unwrap()), Some(&Err(CellError::Cycle)));
+
+    // Breaking the cycle brings the cells back
+    sheet.set("H1", "5").unwrap();
+    assert_eq!(sheet.value(sheet.cell("F1").unwrap()), Some(&Ok(SpreadsheetCell::Int(6))));
+    // Typed values skip parsing and recalculate the same way
+    sheet.set_value("H1", SpreadsheetCell::Float(2.5)).unwrap();
+    assert_eq!(sheet.value(sheet.cell("F1").unwrap()), Some(&Ok(SpreadsheetCell::Float(3.5))));
+    assert_eq!(sheet.input(sheet.cell("H1").unwrap()), Some(String::from("2.5")));
+
+    // Ranges far outside the sheet are an error without every one of their cells being looked at
+    let mut small = Spreadsheet::new(3, 3);
+    small.set("A1", "2").unwrap();
+    small.set("B1", "=SUM(C3:ZZZ2000000)").unwrap();
+    small.set("C1", "=MAX(A1:A999999999)+1").unwrap();
+    small.set("C2", "=SUM(A1:B2)").unwrap();
+    assert_eq!(small.value(small.cell("B1").unwrap()), Some(&Err(CellError::Reference)));
+    assert_eq!(small.value(small.cell("C1").unwrap()), Some(&Err(CellError::Reference)));
+    assert_eq!(small.value(small.cell("C2").unwrap()), Some(&Err(CellError::Reference)));
+    small.set("B1", "=SUM(A1:A3)").unwrap();
+    assert_eq!(small.value(small.cell("C2").unwrap()), Some(&Ok(SpreadsheetCell::Int(4))));
+
+    // Plain grids go through CSV and come back with the same types
+    let grid = vec![
+        vec![SpreadsheetCell::Text(String::from("name, with comma")), SpreadsheetCell::Int(-3)],
+        vec![SpreadsheetCell::Float(2.0), SpreadsheetCell::Text(String::from("say \"hi\""))],
+    ];
+    for &delimiter in [',', ';', '\t'].iter() {
+        let saved = write_csv(&grid, delimiter);
+        assert_eq!(read_csv(&saved, delimiter).unwrap(), grid, "{:?}", saved);
+    }
+    print!("{}", write_csv(&grid, ','));
+    // A row holding only an empty field is not written as an empty line, which would be skipped
+    let grid = vec![vec![SpreadsheetCell::Text(String::new())], vec![SpreadsheetCell::Int(1)]];
+    assert_eq!(write_csv(&grid, ','), "\"\"\n1\n");
+    assert_eq!(read_csv(&write_csv(&grid, ','), ',').unwrap(), grid);
+    assert_eq!(read_csv("a\n\"\"", ',').unwrap().len(), 2);
+    assert_eq!(read_csv("a\n\n", ',').unwrap().len(), 1);
+    assert_eq!(read_csv("a\"b", '"').unwrap_err().to_string(), "'\"' can't be used as a delimiter");
+
+    for formula in ["=1+", "=SUM(A1:3)", "=FOO(1)", "=(1+2", "=1 2", "=A1:B2"].iter() {
+        println!("{:12} {}", formula, sheet.set("A10", formula).unwrap_err());
+    }
+
+    // Deeply nested formulas are an error rather than a stack overflow
+    let nested = |open: &str, depth: usize| format!("{}1{}", open.repeat(depth), ")".repeat(depth));
+    assert!(parse_formula(&nested("(", MAX_DEPTH - 1)).is_ok());
+    assert!(parse_formula(&nested("SUM(", MAX_DEPTH - 1)).is_ok());
+    let error = parse_formula(&nested("(", MAX_DEPTH)).unwrap_err();
+    assert_eq!(error, FormulaError { position: MAX_DEPTH, message: format!("formulas cannot be nested more than {} deep", MAX_DEPTH) });
+    assert!(parse_formula(&"-".repeat(200_000)).is_err());
+    let formula = format!("={}1", "(".repeat(200_000));
+    match sheet.set("A10", &formula) {
+        Err(SheetError::Formula { error, .. }) => assert_eq!(error.position, MAX_DEPTH),
+        other => panic!("{:?}", other),
+    }
+    // Long chains of operators are nested just as deep
+    let chain = |count: usize| format!("1{}", "+1".repeat(count));
+    assert!(parse_formula(&chain(MAX_DEPTH - 1)).is_ok());
+    assert!(parse_formula(&chain(MAX_DEPTH)).is_err());
+    assert!(sheet.set("A10", &format!("={}", chain(200_000))).is_err());
+}
@@ -0,0 +1,279 @@
+// Descriptive statistics over slices of numbers
+//
+// Functions are generic over every type which converts into f64 without loss, so the same code works
+// for Vec<i32>, Vec<u8> or Vec<f64>. Results are f64 and None for an empty slice, where there is nothing to compute.
+// i64, u64 and usize don't implement Into<f64> because large values would lose precision, so they are left out
+// rather than converted with 'as' behind the caller's back.
+// Modes need values which can be counted in a HashMap, floats go through float_modes() instead.
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::fs;
+use std::hash::Hash;
+use std::io::{self, Read};
+
+const HISTOGRAM_BINS: usize = 10;
+const HISTOGRAM_WIDTH: usize = 40;
+// More bins than this wouldn't fit on any screen, and each of them takes memory
+const MAX_BINS: usize = 1000;
+
+fn to_sorted_f64<T: Copy + Into<f64>>(values: &[T]) -> Vec<f64> {
+    let mut sorted: Vec<f64> = values.iter().map(|&v| v.into()).collect();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+pub fn mean<T: Copy + Into<f64>>(values: &[T]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let sum: f64 = values.iter().map(|&v| v.into()).sum();
+    Some(sum / values.len() as f64)
+}
+
+// Middle value, or the mean of the two middle values when the length is even
+pub fn median<T: Copy + Into<f64>>(values: &[T]) -> Option<f64> {
+    let sorted = to_sorted_f64(values);
+    let middle = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(sorted[middle]),
+        _ => Some((sorted[middle - 1] + sorted[middle]) / 2.0),
+    }
+}
+
+// Values occurring most often, counted the same way hashmaps::run() counts words
+// When every value occurs equally often none of them stands out, so there is no mode
+fn most_frequent<T: Copy, K: Eq + Hash>(values: &[T], key: impl Fn(T) -> K) -> Vec<T> {
+    let mut counts: HashMap<K, (T, usize)> = HashMap::new();
+    for &value in values {
+        let count = counts.entry(key(value)).or_insert((value, 0));
+        count.1 += 1;
+    }
+
+    let highest = counts.values().map(|&(_, count)| count).max().unwrap_or(0);
+    if counts.len() > 1 && counts.values().all(|&(_, count)| count == highest) {
+        return Vec::new();
+    }
+    counts.into_values().filter(|&(_, count)| count == highest).map(|(value, _)| value).collect()
+}
+
+// Most frequent values in ascending order, more than one when there is a tie
+pub fn modes<T: Copy + Eq + Hash + Ord>(values: &[T]) -> Vec<T> {
+    let mut found = most_frequent(values, |v| v);
+    found.sort_unstable();
+    found
+}
+
+// f64 is neither Eq nor Hash because of NaN, so floats are counted by their bits
+// -0.0 and 0.0 count as the same value
+pub fn float_modes(values: &[f64]) -> Vec<f64> {
+    let mut found = most_frequent(values, |v| if v == 0.0 { 0u64 } else { v.to_bits() });
+    found.sort_by(f64::total_cmp);
+    found
+}
+
+// Population variance, the mean squared distance from the mean
+pub fn variance<T: Copy + Into<f64>>(values: &[T]) -> Option<f64> {
+    let mean = mean(values)?;
+    let squares: f64 = values.iter().map(|&v| (v.into() - mean).powi(2)).sum();
+    Some(squares / values.len() as f64)
+}
+
+// Variance of a sample, divided by n - 1 to make up for estimating the mean from the sample itself
+pub fn sample_variance<T: Copy + Into<f64>>(values: &[T]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let n = values.len() as f64;
+    variance(values).map(|variance| variance * n / (n - 1.0))
+}
+
+pub fn std_dev<T: Copy + Into<f64>>(values: &[T]) -> Option<f64> {
+    variance(values).map(f64::sqrt)
+}
+
+pub fn sample_std_dev<T: Copy + Into<f64>>(values: &[T]) -> Option<f64> {
+    sample_variance(values).map(f64::sqrt)
+}
+
+// Value below which 'p' percent of the values lie, interpolating linearly between the two closest values
+// like spreadsheets' PERCENTILE does, so percentile(values, 50.0) is the median
+pub fn percentile<T: Copy + Into<f64>>(values: &[T], p: f64) -> Option<f64> {
+    if !(0.0..=100.0).contains(&p) {
+        return None;
+    }
+    let sorted = to_sorted_f64(values);
+    let last = sorted.len().checked_sub(1)?;
+    let rank = p / 100.0 * last as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    Some(sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64))
+}
+
+// Range of values start..end, the last bin also includes its end
+#[derive(Debug, PartialEq)]
+pub struct Bin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+}
+
+// Splits the range from the smallest to the largest value into equally wide bins, at most MAX_BINS of them
+// When all values are the same there is just the one bin
+// 'max - min' overflows for values like -1e308 and 1e308, so edges are mixed from min and max and
+// positions are worked out on halved values, which keeps every finite input finite
+pub fn histogram<T: Copy + Into<f64>>(values: &[T], bins: usize) -> Vec<Bin> {
+    let sorted = to_sorted_f64(values);
+    let (min, max) = match (sorted.first(), sorted.last()) {
+        (Some(&min), Some(&max)) if bins > 0 => (min, max),
+        _ => return Vec::new(),
+    };
+    let bins = if min == max { 1 } else { bins.min(MAX_BINS) };
+    let edge = |i: usize| {
+        let t = i as f64 / bins as f64;
+        (min * (1.0 - t) + max * t).clamp(min, max)
+    };
+    let half_range = max / 2.0 - min / 2.0;
+
+    let mut histogram: Vec<Bin> = (0..bins).map(|i| Bin { start: edge(i), end: edge(i + 1), count: 0 }).collect();
+    for value in sorted {
+        let position = if half_range == 0.0 { 0.0 } else { (value / 2.0 - min / 2.0) / half_range };
+        let i = ((position * bins as f64) as usize).min(bins - 1);
+        histogram[i].count += 1;
+    }
+    histogram
+}
+
+// One line per bin with a bar scaled so that the fullest bin is 'width' chars long
+pub fn render_histogram(histogram: &[Bin], width: usize) -> String {
+    let highest = histogram.iter().map(|bin| bin.count).max().unwrap_or(0).max(1);
+    let labels: Vec<String> = histogram.iter().map(|bin| format!("{:.2} .. {:.2}", bin.start, bin.end)).collect();
+    let label_width = labels.iter().map(|label| label.len()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    for (bin, label) in histogram.iter().zip(labels.iter()) {
+        let bar = "#".repeat(bin.count * width / highest);
+        writeln!(out, "{:>w$} | {:<6} {}", label, bin.count, bar, w = label_width).unwrap();
+    }
+    out
+}
+
+// Everything the 'stats' command prints, given the modes already worked out for the value type
+fn report<T: Copy + Into<f64> + fmt::Display>(values: &[T], modes: &[T], bins: usize) -> String {
+    let mut out = String::new();
+    let sorted = to_sorted_f64(values);
+    let modes: Vec<String> = modes.iter().map(|mode| mode.to_string()).collect();
+    let modes = if modes.is_empty() { String::from("none") } else { modes.join(", ") };
+
+    let mut line = |label: &str, value: String| writeln!(out, "{:<17}{}", format!("{}:", label), value).unwrap();
+    line("Count", values.len().to_string());
+    line("Min", sorted[0].to_string());
+    line("Max", sorted[sorted.len() - 1].to_string());
+    line("Mean", format!("{:.4}", mean(values).unwrap()));
+    line("Median", median(values).unwrap().to_string());
+    line("Modes", modes);
+    line("Variance", format!("{:.4}", variance(values).unwrap()));
+    line("Std dev", format!("{:.4}", std_dev(values).unwrap()));
+    if let Some(sample) = sample_std_dev(values) {
+        line("Sample std dev", format!("{:.4}", sample));
+    }
+    for &p in [25.0, 75.0, 90.0, 99.0].iter() {
+        line(&format!("{}th percentile", p), format!("{:.4}", percentile(values, p).unwrap()));
+    }
+    writeln!(out).unwrap();
+    out += &render_histogram(&histogram(values, bins), HISTOGRAM_WIDTH);
+    out
+}
+
+// Numbers separated by whitespace or commas, with the line of the first one which isn't a number
+fn parse_numbers<T: std::str::FromStr>(input: &str) -> Result<Vec<T>, String> {
+    let mut numbers = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        for token in line.split(|c: char| c.is_whitespace() || c == ',').filter(|token| !token.is_empty()) {
+            let number = token.parse().map_err(|_| format!("line {}: {:?} is not a number", i + 1, token))?;
+            numbers.push(number);
+        }
+    }
+    Ok(numbers)
+}
+
+// 'stats' command: statistics and a histogram of the numbers in a file, or stdin without a file
+pub fn stats(args: &[String]) -> Result<(), String> {
+    const USAGE: &str = "usage: stats [-b <bins>] [file]";
+    let (bins, rest) = match args {
+        [flag, bins, rest @ ..] if flag == "-b" => match bins.parse() {
+            Ok(bins) if bins > 0 && bins <= MAX_BINS => (bins, rest),
+            _ => return Err(format!("number of bins {:?} has to be a number from 1 to {}", bins, MAX_BINS)),
+        },
+        _ => (HISTOGRAM_BINS, args),
+    };
+    let input = match rest {
+        [] => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map_err(|e| e.to_string())?;
+            input
+        }
+        [path] => fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?,
+        _ => return Err(String::from(USAGE)),
+    };
+
+    // Whole numbers are counted exactly, anything else is read as floats
+    let out = match parse_numbers::<i32>(&input) {
+        Ok(numbers) if !numbers.is_empty() => report(&numbers, &modes(&numbers), bins),
+        _ => {
+            let numbers: Vec<f64> = parse_numbers(&input)?;
+            if numbers.is_empty() {
+                return Err(String::from("no numbers to work with"));
+            }
+            if numbers.iter().any(|x| !x.is_finite()) {
+                return Err(String::from("numbers have to be finite"));
+            }
+            report(&numbers, &float_modes(&numbers), bins)
+        }
+    };
+    print!("{}", out);
+    Ok(())
+}
+
+pub fn run() {
+    let values = vec![2, 4, 4, 4, 5, 5, 7, 9];
+    assert_eq!(mean(&values), Some(5.0));
+    assert_eq!(median(&values), Some(4.5));
+    assert_eq!(median(&[3, 1, 2]), Some(2.0));
+    assert_eq!(modes(&values), [4]);
+    assert_eq!(modes(&[1, 1, 2, 2, 3]), [1, 2]);
+    assert_eq!(modes(&[1, 2, 3]), []);
+    assert_eq!(variance(&values), Some(4.0));
+    assert_eq!(std_dev(&values), Some(2.0));
+    assert_eq!(sample_variance(&[1]), None);
+    assert_eq!(percentile(&values, 0.0), Some(2.0));
+    assert_eq!(percentile(&values, 100.0), Some(9.0));
+    assert_eq!(percentile(&values, 50.0), median(&values));
+    assert_eq!(percentile(&values, 101.0), None);
+    assert_eq!(mean::<i32>(&[]), None);
+    assert_eq!(percentile::<i32>(&[], 50.0), None);
+
+    // Same functions on other numeric types
+    let bytes: Vec<u8> = vec![200, 250, 255];
+    assert_eq!(mean(&bytes), Some(235.0));
+    let floats = vec![1.5, -0.0, 0.0, 2.5, 1.5];
+    assert_eq!(median(&floats), Some(1.5));
+    assert_eq!(float_modes(&floats), [0.0, 1.5]);
+    let counts: Vec<usize> = histogram(&values, 3).iter().map(|bin| bin.count).collect();
+    assert_eq!(counts, [4, 2, 2]);
+    assert_eq!(histogram(&[7, 7], 5), [Bin { start: 7.0, end: 7.0, count: 2 }]);
+    assert_eq!(histogram(&values, usize::MAX).len(), MAX_BINS);
+
+    // Values at both ends of f64 still give finite edges in order
+    let extremes = histogram(&[-1e308, 0.0, 1e308], 4);
+    let edges: Vec<(f64, f64)> = extremes.iter().map(|bin| (bin.start, bin.end)).collect();
+    assert_eq!(edges, [(-1e308, -5e307), (-5e307, 0.0), (0.0, 5e307), (5e307, 1e308)]);
+    let counts: Vec<usize> = extremes.iter().map(|bin| bin.count).collect();
+    assert_eq!(counts, [1, 0, 1, 1]);
+    let extremes = histogram(&[f64::MIN, f64::MAX], 7);
+    assert!(extremes.iter().all(|bin| bin.start.is_finite() && bin.end.is_finite() && bin.start <= bin.end));
+    assert_eq!((extremes[0].start, extremes[6].end), (f64::MIN, f64::MAX));
+
+    print!("{}", report(&values, &modes(&values), 4));
+}
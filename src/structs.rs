@@ -1,24 +1,31 @@
+use crate::color::Color;
+use crate::email::{Email, EmailError};
+use crate::point::Point;
+
 pub fn run(){
 
     // Struct Instantiation directly
     let mut user1 = User{
-        email: String::from("someone@example.com"),
+        // Email can only be created by parsing, hence invalid addresses cannot end up in a User
+        email: "someone@example.com".parse().expect("valid email"),
         username: String::from("someone123"),
         active: true,
         sign_in_count: 1
     };
     // To mutate any field, entire struct instance needs to be declared mutable
-    user1.email = String::from("anotherone@example.com");
+    user1.email = "anotherone@example.com".parse().expect("valid email");
     // Debug printing for User struct with derives Debug trait
     println!("user1: {:?}", user1);
 
     // Struct Instantiation using a constructor function
-    let user2 = build_user(String::from("sometwo@example.com"),String::from("sometwo456"));
+    // build_user validates its inputs and hence returns a Result enum
+    let user2 = build_user(String::from("sometwo@example.com"),String::from("sometwo456")).expect("user2 should be valid");
     println!("user2: {:?}", user2);
+    println!("Invalid user: {:?}", build_user(String::from("nobody"),String::from("")).unwrap_err());
 
     // Struct Instantiation using Struct Update Syntax
     let user3 = User{
-        email: String::from("somethree@example.com"),
+        email: "somethree@example.com".parse().expect("valid email"),
         username: String::from("somethree789"),
         ..user1
     };
@@ -27,35 +34,101 @@ pub fn run(){
 
     // Tuple structs: similar to tuples but each of them are their own types like structs
     // Ex: Color and Point are similar in structure but are treated as seperate parameter types for functions,etc
+    // Color is defined in color.rs with u8 channels, so it cannot hold negative or >255 values
+    // Point is defined in point.rs and is generic over its components
     let black = Color(0, 0, 0);
     let origin = Point(0, 0, 0);
 
     println!("black: {:?}", black); // Will work because Debug trait is derived for Color type
-    // println!("{:?}", origin); // Will give error because Debug trait is not derived for Point type
+    println!("origin: {:?}", origin); // Point is defined in point.rs and derives Debug too
     println!("origin: {2},{0},{1}", origin.1, origin.2, origin.0);
 
 }
 
 // Example Struct definition
 #[derive(Debug)] // Deriving Debug trait for printing User struct
-struct User {
+pub struct User {
     username: String,
-    email: String,
+    email: Email,
     sign_in_count: u64,
     active: bool
     // some_other_field: &str // To add references as fields we need lifetimes which will be discussed later
 }
 
-fn build_user(email: String, username: String) -> User {
-    User {
+// Fields are private so other modules read them through methods and can only create Users using build_user
+impl User {
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn email(&self) -> &Email {
+        &self.email
+    }
+
+    pub fn sign_in_count(&self) -> u64 {
+        self.sign_in_count
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn record_sign_in(&mut self) {
+        self.sign_in_count += 1;
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+}
+
+// Reasons for which build_user can reject its inputs
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserError {
+    EmptyUsername,
+    SurroundingWhitespace,
+    InvalidUsernameChar(char),
+    InvalidEmail(EmailError),
+}
+
+impl std::fmt::Display for UserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            UserError::EmptyUsername => write!(f, "username cannot be empty"),
+            UserError::SurroundingWhitespace => write!(f, "username cannot start or end with whitespace"),
+            UserError::InvalidUsernameChar(c) => write!(f, "username cannot contain {:?}", c),
+            UserError::InvalidEmail(e) => write!(f, "invalid email: {}", e),
+        }
+    }
+}
+
+pub fn build_user(email: String, username: String) -> Result<User, UserError> {
+    if username.trim().is_empty() {
+        return Err(UserError::EmptyUsername);
+    }
+    // " bob " would look the same as "bob" in every listing
+    if username.trim() != username {
+        return Err(UserError::SurroundingWhitespace);
+    }
+    // Otherwise usernames can contain any printable characters, including commas, quotes and non-ASCII letters
+    if let Some(c) = username.chars().find(|c| c.is_control()) {
+        return Err(UserError::InvalidUsernameChar(c));
+    }
+    let email = Email::parse(&email).map_err(UserError::InvalidEmail)?;
+
+    Ok(User {
         // Field init shorthand syntax since parameter names and field names are same
         email,
         username,
         sign_in_count: 1,
         active: true
-    }
+    })
 }
 
-#[derive(Debug)] // Deriving Debug trait for printing Color tuple struct
-struct Color(i32, i32, i32);
-struct Point(i32, i32, i32);
+// Recreates a previously saved User, validating it the same way as build_user
+pub fn restore_user(email: String, username: String, sign_in_count: u64, active: bool) -> Result<User, UserError> {
+    let mut user = build_user(email, username)?;
+    user.sign_in_count = sign_in_count;
+    user.active = active;
+    Ok(user)
+}
@@ -0,0 +1,107 @@
+// Prints rows of SpreadsheetCells as a table with aligned columns
+// Columns are measured in display width rather than chars, see width.rs
+
+use crate::collections::vectors::SpreadsheetCell;
+use crate::csv;
+use crate::spreadsheet::read_csv;
+use crate::width::{display_width, pad};
+use std::fs;
+use std::io::{self, Read};
+
+fn is_number(cell: &SpreadsheetCell) -> bool {
+    !matches!(cell, SpreadsheetCell::Text(_))
+}
+
+// First row is the header, numbers are right-aligned and text left-aligned
+// Rows shorter than the longest one are filled up with empty cells
+pub fn render(rows: &[Vec<SpreadsheetCell>]) -> String {
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in rows {
+        for (column, cell) in row.iter().enumerate() {
+            widths[column] = widths[column].max(display_width(&cell.to_string()));
+        }
+    }
+
+    let mut out = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(column, &width)| match row.get(column) {
+                Some(cell) => pad(&cell.to_string(), width, i > 0 && is_number(cell)),
+                None => " ".repeat(width),
+            })
+            .collect();
+        out += cells.join(" | ").trim_end();
+        out.push('\n');
+        if i == 0 {
+            let rule: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+            out += &rule.join("-+-");
+            out.push('\n');
+        }
+    }
+    out
+}
+
+// Delimiter given on the command line, "\t" and "tab" both mean a tab
+fn parse_delimiter(arg: &str) -> Result<char, String> {
+    let delimiter = match arg {
+        "\\t" | "tab" => '\t',
+        _ => {
+            let mut chars = arg.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(format!("delimiter {:?} has to be a single char", arg)),
+            }
+        }
+    };
+    if !csv::is_valid_delimiter(delimiter) {
+        return Err(format!("{:?} can't be used as a delimiter", delimiter));
+    }
+    Ok(delimiter)
+}
+
+// 'table' command: prints a CSV file, or stdin without a file, as an aligned table
+pub fn table(args: &[String]) -> Result<(), String> {
+    const USAGE: &str = "usage: table [-d <delimiter>] [file]";
+    let (delimiter, rest) = match args {
+        [flag, delimiter, rest @ ..] if flag == "-d" => (parse_delimiter(delimiter)?, rest),
+        _ => (csv::DEFAULT_DELIMITER, args),
+    };
+    let input = match rest {
+        [] => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map_err(|e| e.to_string())?;
+            input
+        }
+        [path] => fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?,
+        _ => return Err(String::from(USAGE)),
+    };
+    let rows = read_csv(&input, delimiter).map_err(|e| e.to_string())?;
+    print!("{}", render(&rows));
+    Ok(())
+}
+
+pub fn run() {
+    assert_eq!(display_width("abc"), 3);
+    assert_eq!(display_width("日本語"), 6);
+    assert_eq!(display_width("e\u{301}"), 1);
+    assert_eq!(display_width("👍🏽 ok"), 5);
+    assert_eq!(display_width("👩\u{200d}💻"), 2);
+    assert_eq!(display_width("🇮🇳"), 2);
+    assert_eq!(display_width("ｆｕｌｌ"), 8);
+    assert_eq!(pad("日本", 6, false), "日本  ");
+    assert_eq!(pad("42", 4, true), "  42");
+
+    let input = "City;Country;Population;Area\n東京;日本;13960000;2194.07\nMünchen;Deutschland;1488000;310.7\n\
+                 \"São Paulo\";Brasil 🇧🇷;12330000;1521.1\n\"Semi;colon\";\"Quoted \"\"name\"\"\";0;\n";
+    let rows = read_csv(input, ';').unwrap();
+    assert_eq!(rows[1][2], SpreadsheetCell::Int(13960000));
+    assert_eq!(rows[4][0], SpreadsheetCell::Text(String::from("Semi;colon")));
+    print!("{}", render(&rows));
+
+    for arg in ["\\t", ";", "ab", "\""].iter() {
+        println!("delimiter {:?}: {:?}", arg, parse_delimiter(arg));
+    }
+}
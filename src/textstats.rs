@@ -0,0 +1,173 @@
+// Text statistics where every word and sentence is a &str slice of the one buffer holding the file
+// Slices are just a pointer and a length, so nothing is copied however many words there are
+
+use crate::tokenizer::words;
+use std::fs;
+use std::mem::size_of;
+
+const LONGEST_WORDS: usize = 5;
+
+// Borrows from the text it was built from, so it cannot outlive that text
+pub struct TextStats<'a> {
+    text: &'a str,
+    pub words: Vec<&'a str>,
+    pub sentences: Vec<&'a str>,
+    pub lines: usize,
+    pub chars: usize,
+}
+
+fn ends_sentence(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '\u{964}' | '\u{965}' | '\u{3002}' | '\u{ff01}' | '\u{ff1f}')
+}
+
+// Chinese and Japanese don't put spaces between sentences, so these full-width terminators end one on their own
+fn ends_sentence_without_space(c: char) -> bool {
+    matches!(c, '\u{3002}' | '\u{ff01}' | '\u{ff1f}')
+}
+
+// Sentences end with one or more terminators like '.', '!', '?', '।' or '。' followed by whitespace or the end of the text,
+// so the dot in "3.14" doesn't split a sentence. A run ending in '。', '！' or '？' needs no whitespace after it.
+fn sentences(text: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !ends_sentence(c) {
+            continue;
+        }
+        // Takes in the whole run of terminators, as in "Really?!"
+        let mut end = i + c.len_utf8();
+        let mut last = c;
+        while let Some(&(j, next)) = chars.peek() {
+            if !ends_sentence(next) {
+                break;
+            }
+            end = j + next.len_utf8();
+            last = next;
+            chars.next();
+        }
+        let at_boundary =
+            ends_sentence_without_space(last) || chars.peek().is_none_or(|&(_, next)| next.is_whitespace());
+        if at_boundary {
+            found.push(&text[start..end]);
+            start = end;
+        }
+    }
+    // Text after the last terminator is a sentence too
+    found.push(&text[start..]);
+    found.into_iter().map(|sentence| sentence.trim()).filter(|sentence| !sentence.is_empty()).collect()
+}
+
+impl<'a> TextStats<'a> {
+    pub fn new(text: &'a str) -> TextStats<'a> {
+        TextStats {
+            text,
+            words: words(text).collect(),
+            sentences: sentences(text),
+            lines: text.lines().count(),
+            chars: text.chars().count(),
+        }
+    }
+
+    // Average number of chars per word
+    pub fn average_word_length(&self) -> f64 {
+        if self.words.is_empty() {
+            return 0.0;
+        }
+        let total: usize = self.words.iter().map(|word| word.chars().count()).sum();
+        total as f64 / self.words.len() as f64
+    }
+
+    // Longest distinct words, ties in alphabetical order
+    pub fn longest_words(&self, n: usize) -> Vec<&'a str> {
+        let mut sorted = self.words.clone();
+        sorted.sort_by(|a, b| b.chars().count().cmp(&a.chars().count()).then(a.cmp(b)));
+        sorted.dedup();
+        sorted.truncate(n);
+        sorted
+    }
+
+    // Byte range of a slice within the text, found from where the slice points to
+    pub fn byte_range(&self, slice: &str) -> (usize, usize) {
+        let start = slice.as_ptr() as usize - self.text.as_ptr() as usize;
+        (start, start + slice.len())
+    }
+
+    // Heap bytes used by the word and sentence lists, the slices themselves point into the text
+    pub fn heap_bytes(&self) -> usize {
+        (self.words.capacity() + self.sentences.capacity()) * size_of::<&str>()
+    }
+}
+
+// Same lists built from owned Strings, only there to compare the memory use
+struct OwnedStats {
+    words: Vec<String>,
+    sentences: Vec<String>,
+}
+
+impl OwnedStats {
+    fn new(text: &str) -> OwnedStats {
+        OwnedStats {
+            words: words(text).map(String::from).collect(),
+            sentences: sentences(text).into_iter().map(String::from).collect(),
+        }
+    }
+
+    // Heap bytes of both lists plus the copy of every word and sentence
+    fn heap_bytes(&self) -> usize {
+        let lists = (self.words.capacity() + self.sentences.capacity()) * size_of::<String>();
+        let strings: usize = self.words.iter().chain(self.sentences.iter()).map(|s| s.capacity()).sum();
+        lists + strings
+    }
+}
+
+fn report(stats: &TextStats) {
+    println!("Lines:      {}", stats.lines);
+    println!("Sentences:  {}", stats.sentences.len());
+    println!("Words:      {}", stats.words.len());
+    println!("Chars:      {}", stats.chars);
+    println!("Bytes:      {}", stats.text.len());
+    println!("Average word length: {:.2}", stats.average_word_length());
+    println!("Longest words: {}", stats.longest_words(LONGEST_WORDS).join(", "));
+
+    println!();
+    for sentence in &stats.sentences {
+        let (start, end) = stats.byte_range(sentence);
+        let preview: String = sentence.chars().take(50).collect();
+        let more = if preview.len() < sentence.len() { "..." } else { "" };
+        println!("{:>7}..{:<7} {}{}", start, end, preview.replace('\n', " "), more);
+    }
+
+    let owned = OwnedStats::new(stats.text);
+    println!();
+    println!("Memory for words and sentences:");
+    println!("  borrowed slices: {} bytes", stats.heap_bytes());
+    println!("  owned Strings:   {} bytes", owned.heap_bytes());
+}
+
+// 'textstats' command: statistics of the given text file
+pub fn textstats(args: &[String]) -> Result<(), String> {
+    let path = match args {
+        [path] => path,
+        _ => return Err(String::from("usage: textstats <file>")),
+    };
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let stats = TextStats::new(&text);
+    report(&stats);
+    Ok(())
+}
+
+pub fn run() {
+    let text = "Rust is fast. Is it safe? Yes!\nIt costs 3.14 units, nothing more.\nनमस्ते दुनिया। Done";
+    let stats = TextStats::new(text);
+    assert_eq!(stats.sentences, ["Rust is fast.", "Is it safe?", "Yes!", "It costs 3.14 units, nothing more.", "नमस्ते दुनिया।", "Done"]);
+    assert_eq!(stats.lines, 3);
+    assert_eq!(stats.longest_words(2), ["nothing", "दुनिया"]);
+    assert_eq!(sentences("你好。世界。再见。"), ["你好。", "世界。", "再见。"]);
+    assert_eq!(sentences("你好。世界！再见？真的?！好"), ["你好。", "世界！", "再见？", "真的?！", "好"]);
+
+    // Every word points into 'text' itself
+    let (start, end) = stats.byte_range(stats.words[2]);
+    assert_eq!(&text[start..end], "fast");
+    report(&stats);
+}
@@ -0,0 +1,137 @@
+// Zero-copy word tokenizer: every word is a &str slice borrowing from the input, nothing is allocated
+//
+// Words are separated by Unicode whitespace (tabs, line breaks, no-break spaces, ...), punctuation and symbols.
+// Everything else belongs to a word, including the combining marks of scripts like Devanagari,
+// so "नमस्ते" stays one word even though it is made of six chars.
+// An apostrophe between two letters is part of the word, which keeps "don't" together.
+
+// Whether the char is punctuation (Unicode categories P*) or a symbol (S*) such as '€' or '©'
+// std only knows ASCII punctuation, which includes the ASCII symbols like '$' and '+', and has no Unicode
+// category tables. So this is a subset: the blocks below hold most punctuation and symbols in use,
+// rarer ones from other blocks (for example letterlike symbols like '℃') still count as part of a word.
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(c,
+            // Latin-1 punctuation: ¡ § « ¶ · » ¿
+            '\u{a1}' | '\u{a7}' | '\u{ab}' | '\u{b6}' | '\u{b7}' | '\u{bb}' | '\u{bf}'
+            // Latin-1 symbols: ¢ £ ¤ ¥ ¦ ¨ © ¬ ® ¯ ° ± ´ ¸ × ÷
+            | '\u{a2}'..='\u{a6}' | '\u{a8}' | '\u{a9}' | '\u{ac}' | '\u{ae}'..='\u{b1}' | '\u{b4}' | '\u{b8}'
+            | '\u{d7}' | '\u{f7}'
+            // Arabic comma, semicolon and question mark
+            | '\u{60c}' | '\u{61b}' | '\u{61f}'
+            // Devanagari danda and double danda
+            | '\u{964}' | '\u{965}'
+            // General Punctuation without the zero width (non-)joiners, which are used inside words
+            | '\u{200b}' | '\u{200e}'..='\u{206f}'
+            | '\u{2e00}'..='\u{2e7f}'
+            // Currency symbols such as € and ₹
+            | '\u{20a0}'..='\u{20cf}'
+            // Arrows, mathematical operators, technical symbols, box drawing, shapes, dingbats
+            | '\u{2190}'..='\u{23ff}' | '\u{2500}'..='\u{27bf}' | '\u{2900}'..='\u{2bff}'
+            // Emoji and pictographs, without the skin tones which belong to the emoji before them
+            | '\u{1f300}'..='\u{1f3fa}' | '\u{1f400}'..='\u{1faff}'
+            // CJK symbols and punctuation such as 、 and 。
+            | '\u{3000}'..='\u{303f}'
+            // Fullwidth forms of ASCII punctuation such as ， and ！
+            | '\u{ff01}'..='\u{ff0f}' | '\u{ff1a}'..='\u{ff20}' | '\u{ff3b}'..='\u{ff40}' | '\u{ff5b}'..='\u{ff65}')
+}
+
+pub fn is_separator(c: char) -> bool {
+    c.is_whitespace() || is_punctuation(c)
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '\u{2019}'
+}
+
+// Whether the char starting at byte 'i' of the text belongs to a word
+fn in_word(text: &str, i: usize, c: char) -> bool {
+    if !is_separator(c) {
+        return true;
+    }
+    let before = text[..i].chars().next_back();
+    let after = text[i + c.len_utf8()..].chars().next();
+    let letter = |c: Option<char>| c.is_some_and(|c| !is_separator(c));
+    is_apostrophe(c) && letter(before) && letter(after)
+}
+
+// Iterator over the words of a text, from either end
+// 'front' and 'back' are byte offsets of the part of the text which hasn't been handed out yet
+pub struct Words<'a> {
+    text: &'a str,
+    front: usize,
+    back: usize,
+}
+
+pub fn words(text: &str) -> Words<'_> {
+    Words { text, front: 0, back: text.len() }
+}
+
+impl<'a> Iterator for Words<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = &self.text[self.front..self.back];
+        let mut chars = rest.char_indices().map(|(i, c)| (self.front + i, c));
+        let (start, _) = chars.find(|&(i, c)| in_word(self.text, i, c))?;
+        let end = chars.find(|&(i, c)| !in_word(self.text, i, c)).map_or(self.back, |(i, _)| i);
+        self.front = end;
+        Some(&self.text[start..end])
+    }
+}
+
+impl<'a> DoubleEndedIterator for Words<'a> {
+    fn next_back(&mut self) -> Option<&'a str> {
+        let rest = &self.text[self.front..self.back];
+        let mut chars = rest.char_indices().rev().map(|(i, c)| (self.front + i, c));
+        let (last, c) = chars.find(|&(i, c)| in_word(self.text, i, c))?;
+        let end = last + c.len_utf8();
+        let start = chars.find(|&(i, c)| !in_word(self.text, i, c)).map_or(self.front, |(i, c)| i + c.len_utf8());
+        self.back = start;
+        Some(&self.text[start..end])
+    }
+}
+
+pub fn first_word(text: &str) -> Option<&str> {
+    words(text).next()
+}
+
+// Word at index n, counting from 0
+pub fn nth_word(text: &str, n: usize) -> Option<&str> {
+    words(text).nth(n)
+}
+
+// Reads the text from the end, so only the last word is looked at
+pub fn last_word(text: &str) -> Option<&str> {
+    words(text).next_back()
+}
+
+pub fn run() {
+    let text = "  Hello,\tworld!\nThis\u{a0}is  Rust. ";
+    let found: Vec<&str> = words(text).collect();
+    println!("{:?} -> {:?}", text, found);
+    assert_eq!(found, ["Hello", "world", "This", "is", "Rust"]);
+
+    // Combining marks stay with their letters and the danda ends a sentence
+    let hindi = "नमस्ते दुनिया। आप कैसे हैं?";
+    assert_eq!(first_word(hindi), Some("नमस्ते"));
+    assert_eq!(nth_word(hindi, 1), Some("दुनिया"));
+    assert_eq!(last_word(hindi), Some("हैं"));
+    assert_eq!(words(hindi).count(), 5);
+
+    assert_eq!(words("Don't panic, it’s fine 'quoted'").collect::<Vec<&str>>(), ["Don't", "panic", "it’s", "fine", "quoted"]);
+    assert_eq!(words("你好，世界！").collect::<Vec<&str>>(), ["你好", "世界"]);
+    assert_eq!(words("« bonjour » — ¿qué?").rev().collect::<Vec<&str>>(), ["qué", "bonjour"]);
+    assert_eq!(first_word(" ... \n "), None);
+    assert_eq!(words("€5 © 2024 price→value ½").collect::<Vec<&str>>(), ["5", "2024", "price", "value", "½"]);
+    assert_eq!(nth_word("one two", 2), None);
+
+    // Words are slices of the input, so they point into the same memory
+    let sentence = String::from("borrowed not copied");
+    let second = nth_word(&sentence, 1).unwrap();
+    assert_eq!(second.as_ptr(), sentence[9..].as_ptr());
+
+    // Taking words from both ends meets in the middle without handing out a word twice
+    let mut both = words("a b c d e");
+    println!("{:?} {:?} {:?} {:?}", both.next(), both.next_back(), both.next(), both.collect::<Vec<&str>>());
+}
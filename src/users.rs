@@ -0,0 +1,431 @@
+// User registry built on top of the User struct from structs.rs
+// Keeps track of all created Users and their sign-ins
+
+use crate::csv;
+use crate::json::{self, Value};
+use crate::structs::{build_user, restore_user, User, UserError};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, PartialEq)]
+pub enum RegistryError {
+    InvalidUser(UserError),
+    DuplicateUsername(String),
+    DuplicateEmail(String),
+    UnknownUser(String),
+    InactiveUser(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::InvalidUser(e) => write!(f, "invalid user: {}", e),
+            RegistryError::DuplicateUsername(name) => write!(f, "username {:?} is already taken", name),
+            RegistryError::DuplicateEmail(email) => write!(f, "email {:?} is already registered", email),
+            RegistryError::UnknownUser(name) => write!(f, "no user named {:?}", name),
+            RegistryError::InactiveUser(name) => write!(f, "user {:?} is deactivated", name),
+        }
+    }
+}
+
+// Allows using the ? operator on build_user results inside registry methods
+impl From<UserError> for RegistryError {
+    fn from(e: UserError) -> RegistryError {
+        RegistryError::InvalidUser(e)
+    }
+}
+
+// Errors which can happen while loading a saved registry
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    Csv(csv::ParseError),
+    Json(json::ParseError),
+    // File is well-formed but one of its users is not, 'location' tells where it is
+    InvalidRecord { location: String, message: String },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::Io(e) => write!(f, "{}", e),
+            StorageError::Csv(e) => write!(f, "malformed CSV at {}", e),
+            StorageError::Json(e) => write!(f, "malformed JSON at {}", e),
+            StorageError::InvalidRecord { location, message } => write!(f, "{}: {}", location, message),
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(e: io::Error) -> StorageError {
+        StorageError::Io(e)
+    }
+}
+
+impl From<csv::ParseError> for StorageError {
+    fn from(e: csv::ParseError) -> StorageError {
+        StorageError::Csv(e)
+    }
+}
+
+impl From<json::ParseError> for StorageError {
+    fn from(e: json::ParseError) -> StorageError {
+        StorageError::Json(e)
+    }
+}
+
+fn invalid_record<T>(location: String, message: String) -> Result<T, StorageError> {
+    Err(StorageError::InvalidRecord { location, message })
+}
+
+const CSV_HEADER: [&str; 4] = ["username", "email", "sign_in_count", "active"];
+
+#[derive(Debug, Default)]
+pub struct UserRegistry {
+    // Users are owned by the registry and looked up by their username
+    users: HashMap<String, User>,
+    // Email to username index for rejecting duplicate emails
+    emails: HashMap<String, String>,
+}
+
+impl UserRegistry {
+    pub fn new() -> UserRegistry {
+        UserRegistry::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    pub fn get(&self, username: &str) -> Option<&User> {
+        self.users.get(username)
+    }
+
+    // Creates a User through build_user and takes ownership of it
+    pub fn register(&mut self, email: String, username: String) -> Result<&User, RegistryError> {
+        let user = build_user(email, username)?;
+        self.insert(user)
+    }
+
+    // Takes ownership of an already built User after checking for duplicates
+    pub fn insert(&mut self, user: User) -> Result<&User, RegistryError> {
+        if self.users.contains_key(user.username()) {
+            return Err(RegistryError::DuplicateUsername(user.username().to_string()));
+        }
+        // Email domains are already lowercase, local parts are compared case-insensitively too
+        let email_key = user.email().as_str().to_lowercase();
+        if self.emails.contains_key(&email_key) {
+            return Err(RegistryError::DuplicateEmail(user.email().to_string()));
+        }
+
+        let username = user.username().to_string();
+        self.emails.insert(email_key, username.clone());
+        Ok(self.users.entry(username).or_insert(user))
+    }
+
+    // Returns the updated sign-in count
+    pub fn sign_in(&mut self, username: &str) -> Result<u64, RegistryError> {
+        let user = self.get_mut(username)?;
+        if !user.is_active() {
+            return Err(RegistryError::InactiveUser(username.to_string()));
+        }
+        user.record_sign_in();
+        Ok(user.sign_in_count())
+    }
+
+    pub fn deactivate(&mut self, username: &str) -> Result<(), RegistryError> {
+        self.get_mut(username)?.set_active(false);
+        Ok(())
+    }
+
+    pub fn reactivate(&mut self, username: &str) -> Result<(), RegistryError> {
+        self.get_mut(username)?.set_active(true);
+        Ok(())
+    }
+
+    // Users sorted by username so that listings are stable
+    pub fn users(&self) -> Vec<&User> {
+        let mut users: Vec<&User> = self.users.values().collect();
+        users.sort_by(|a, b| a.username().cmp(b.username()));
+        users
+    }
+
+    pub fn active_users(&self) -> Vec<&User> {
+        self.users().into_iter().filter(|u| u.is_active()).collect()
+    }
+
+    // Up to 'n' users with the most sign-ins, ties are broken by username
+    pub fn most_active(&self, n: usize) -> Vec<&User> {
+        let mut users = self.users();
+        users.sort_by_key(|u| Reverse(u.sign_in_count()));
+        users.truncate(n);
+        users
+    }
+
+    fn get_mut(&mut self, username: &str) -> Result<&mut User, RegistryError> {
+        self.users
+            .get_mut(username)
+            .ok_or_else(|| RegistryError::UnknownUser(username.to_string()))
+    }
+
+    // One header line followed by one line per user
+    pub fn to_csv(&self) -> String {
+        let mut out = csv::write_record(&CSV_HEADER);
+        for user in self.users() {
+            let count = user.sign_in_count().to_string();
+            let active = user.is_active().to_string();
+            out += &csv::write_record(&[user.username(), user.email().as_str(), &count, &active]);
+        }
+        out
+    }
+
+    pub fn from_csv(input: &str) -> Result<UserRegistry, StorageError> {
+        let mut registry = UserRegistry::new();
+        let mut records = csv::parse(input)?.into_iter();
+
+        match records.next() {
+            None => return Ok(registry),
+            Some(header) if header.fields == CSV_HEADER => (),
+            Some(header) => {
+                let location = format!("line {}", header.line);
+                return invalid_record(location, format!("expected header {:?}", CSV_HEADER.join(",")));
+            }
+        }
+
+        for record in records {
+            let at = |field: usize| format!("line {}, column {}", record.line, record.columns[field]);
+            if record.fields.len() != CSV_HEADER.len() {
+                let message = format!("expected {} fields but found {}", CSV_HEADER.len(), record.fields.len());
+                return invalid_record(at(0), message);
+            }
+
+            let sign_in_count = match record.fields[2].parse() {
+                Ok(count) => count,
+                Err(_) => return invalid_record(at(2), format!("{:?} is not a sign-in count", record.fields[2])),
+            };
+            let active = match record.fields[3].as_str() {
+                "true" => true,
+                "false" => false,
+                other => return invalid_record(at(3), format!("{:?} should be true or false", other)),
+            };
+
+            let (username, email) = (record.fields[0].clone(), record.fields[1].clone());
+            let user = match restore_user(email, username, sign_in_count, active) {
+                Ok(user) => user,
+                Err(e @ UserError::InvalidEmail(_)) => return invalid_record(at(1), e.to_string()),
+                Err(e) => return invalid_record(at(0), e.to_string()),
+            };
+            if let Err(e) = registry.insert(user) {
+                return invalid_record(at(0), e.to_string());
+            }
+        }
+        Ok(registry)
+    }
+
+    // An array with one object per user, each on its own line
+    pub fn to_json(&self) -> String {
+        let objects: Vec<String> = self
+            .users()
+            .into_iter()
+            .map(|user| {
+                let object = Value::Object(vec![
+                    (String::from("username"), Value::from(user.username())),
+                    (String::from("email"), Value::from(user.email().as_str())),
+                    (String::from("sign_in_count"), Value::from(user.sign_in_count())),
+                    (String::from("active"), Value::from(user.is_active())),
+                ]);
+                format!("  {}", object)
+            })
+            .collect();
+
+        if objects.is_empty() {
+            String::from("[]\n")
+        } else {
+            format!("[\n{}\n]\n", objects.join(",\n"))
+        }
+    }
+
+    pub fn from_json(input: &str) -> Result<UserRegistry, StorageError> {
+        let mut registry = UserRegistry::new();
+        for element in json::parse_elements(input)? {
+            let location = || format!("line {}, column {}", element.line, element.column);
+            let field = |name: &str| match element.value.get(name) {
+                Some(value) => Ok(value),
+                None => invalid_record(location(), format!("missing field {:?}", name)),
+            };
+
+            let username = field("username")?.as_str();
+            let email = field("email")?.as_str();
+            let sign_in_count = field("sign_in_count")?.as_u64();
+            let active = field("active")?.as_bool();
+
+            let user = match (username, email, sign_in_count, active) {
+                (Some(username), Some(email), Some(count), Some(active)) => {
+                    restore_user(email.to_string(), username.to_string(), count, active)
+                }
+                _ => return invalid_record(location(), String::from("field has the wrong type")),
+            };
+            let user = match user {
+                Ok(user) => user,
+                Err(e) => return invalid_record(location(), e.to_string()),
+            };
+            if let Err(e) = registry.insert(user) {
+                return invalid_record(location(), e.to_string());
+            }
+        }
+        Ok(registry)
+    }
+
+    pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+
+    pub fn load_csv<P: AsRef<Path>>(path: P) -> Result<UserRegistry, StorageError> {
+        UserRegistry::from_csv(&fs::read_to_string(path)?)
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<UserRegistry, StorageError> {
+        UserRegistry::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+pub fn run() {
+    let mut registry = UserRegistry::new();
+    assert!(registry.is_empty());
+
+    registry.register(String::from("someone@example.com"), String::from("someone123")).unwrap();
+    registry.register(String::from("sometwo@example.com"), String::from("sometwo456")).unwrap();
+    registry.register(String::from("somethree@example.com"), String::from("somethree789")).unwrap();
+    assert_eq!(registry.len(), 3);
+    assert_eq!(registry.get("sometwo456").unwrap().email().as_str(), "sometwo@example.com");
+    assert_eq!(registry.get("sometwo456").unwrap().sign_in_count(), 1);
+
+    // Duplicate and invalid registrations are rejected and leave the registry as it was
+    let duplicate = registry.register(String::from("other@example.com"), String::from("someone123"));
+    assert_eq!(duplicate.unwrap_err(), RegistryError::DuplicateUsername(String::from("someone123")));
+    let duplicate = registry.register(String::from("SOMEONE@example.com"), String::from("someone_else"));
+    println!("Duplicate email: {}", duplicate.as_ref().unwrap_err());
+    assert_eq!(duplicate.unwrap_err(), RegistryError::DuplicateEmail(String::from("SOMEONE@example.com")));
+    let invalid = registry.register(String::from("not-an-email"), String::from("nobody"));
+    println!("Invalid email: {}", invalid.as_ref().unwrap_err());
+    assert!(matches!(invalid, Err(RegistryError::InvalidUser(UserError::InvalidEmail(_)))));
+    let invalid = registry.register(String::from("nobody@example.com"), String::new());
+    assert_eq!(invalid.unwrap_err(), RegistryError::InvalidUser(UserError::EmptyUsername));
+    assert_eq!(registry.len(), 3);
+    assert!(registry.get("someone_else").is_none());
+
+    // Every sign-in increments the count, which starts at 1 when the user is built
+    for expected in 2..=4 {
+        assert_eq!(registry.sign_in("sometwo456"), Ok(expected));
+    }
+    assert_eq!(registry.sign_in("someone123"), Ok(2));
+    assert_eq!(registry.sign_in("nobody"), Err(RegistryError::UnknownUser(String::from("nobody"))));
+
+    // Deactivated users cannot sign in until they are reactivated
+    registry.deactivate("somethree789").unwrap();
+    let inactive = registry.sign_in("somethree789");
+    println!("Sign in while deactivated: {}", inactive.as_ref().unwrap_err());
+    assert_eq!(inactive, Err(RegistryError::InactiveUser(String::from("somethree789"))));
+    assert_eq!(registry.get("somethree789").unwrap().sign_in_count(), 1);
+    let active: Vec<&str> = registry.active_users().iter().map(|u| u.username()).collect();
+    assert_eq!(active, ["someone123", "sometwo456"]);
+    registry.reactivate("somethree789").unwrap();
+    assert_eq!(registry.sign_in("somethree789"), Ok(2));
+    assert_eq!(registry.deactivate("nobody"), Err(RegistryError::UnknownUser(String::from("nobody"))));
+
+    // someone123 and somethree789 both have 2 sign-ins, the tie goes to the username which sorts first
+    println!("Most active users:");
+    for user in registry.most_active(2) {
+        println!("\t{} ({} sign-ins)", user.username(), user.sign_in_count());
+    }
+    let most_active: Vec<&str> = registry.most_active(3).iter().map(|u| u.username()).collect();
+    assert_eq!(most_active, ["sometwo456", "someone123", "somethree789"]);
+    assert_eq!(registry.most_active(1).len(), 1);
+    assert_eq!(registry.most_active(10).len(), 3);
+
+    // Usernames with commas, quotes and non-ASCII letters survive both formats unchanged
+    registry.register(String::from("quote@example.com"), String::from("Smith, \"Agent\"")).unwrap();
+    registry.register(String::from("namaste@example.com"), String::from("नमस्ते_José")).unwrap();
+    registry.deactivate("sometwo456").unwrap();
+    let csv = registry.to_csv();
+    let json = registry.to_json();
+    println!("CSV:\n{}", csv);
+    println!("JSON:\n{}", json);
+    assert!(csv.contains("\"Smith, \"\"Agent\"\"\""));
+
+    let from_csv = UserRegistry::from_csv(&csv).expect("saved CSV should load");
+    let from_json = UserRegistry::from_json(&json).expect("saved JSON should load");
+    let fields = |registry: &UserRegistry| -> Vec<(String, String, u64, bool)> {
+        registry
+            .users()
+            .iter()
+            .map(|u| (u.username().to_string(), u.email().to_string(), u.sign_in_count(), u.is_active()))
+            .collect()
+    };
+    assert_eq!(fields(&from_csv), fields(&registry));
+    assert_eq!(fields(&from_json), fields(&registry));
+    assert_eq!(from_csv.get("नमस्ते_José").unwrap().email().as_str(), "namaste@example.com");
+    assert_eq!(UserRegistry::from_csv("").unwrap().len(), 0);
+    assert_eq!(UserRegistry::from_json("[]").unwrap().len(), 0);
+
+    // Files go to the temporary directory so that nothing is left behind in the working directory
+    let path = std::env::temp_dir().join(format!("users-{}.csv", std::process::id()));
+    registry.save_csv(&path).expect("temporary file should be writable");
+    let loaded = UserRegistry::load_csv(&path);
+    fs::remove_file(&path).expect("file was just written");
+    assert_eq!(fields(&loaded.expect("saved file should load")), fields(&registry));
+    let path = path.with_extension("json");
+    registry.save_json(&path).expect("temporary file should be writable");
+    let loaded = UserRegistry::load_json(&path);
+    fs::remove_file(&path).expect("file was just written");
+    assert_eq!(fields(&loaded.expect("saved file should load")), fields(&registry));
+
+    // Blank usernames and ones with whitespace around them are rejected
+    let rejected = [
+        ("   ", UserError::EmptyUsername),
+        (" bob ", UserError::SurroundingWhitespace),
+        ("bob\t", UserError::SurroundingWhitespace),
+    ];
+    for (username, error) in rejected.iter() {
+        let result = registry.register(String::from("bob@example.com"), username.to_string());
+        assert_eq!(result.unwrap_err(), RegistryError::InvalidUser(error.clone()));
+    }
+
+    let broken = "username,email,sign_in_count,active\nsomeone,someone@example.com,many,true\n";
+    let error = UserRegistry::from_csv(broken).unwrap_err();
+    println!("Loading broken CSV: {}", error);
+    assert_eq!(error.to_string(), "line 2, column 29: \"many\" is not a sign-in count");
+    let broken = "username,email,sign_in_count,active\n\"someone,someone@example.com,1,true\n";
+    assert_eq!(UserRegistry::from_csv(broken).unwrap_err().to_string(), "malformed CSV at line 2, column 1: quoted field is never closed");
+
+    // JSON errors point at the user they were found in
+    let broken = "[\n  {\"username\": \"a\", \"email\": \"a@example.com\", \"sign_in_count\": 1, \"active\": true},\n  \
+                  {\"username\": \"b\", \"email\": \"b@example.com\", \"sign_in_count\": \"many\", \"active\": true}\n]\n";
+    let error = UserRegistry::from_json(broken).unwrap_err();
+    println!("Loading broken JSON: {}", error);
+    assert_eq!(error.to_string(), "line 3, column 3: field has the wrong type");
+    let broken = "[{\"username\": \"a\"}]";
+    assert_eq!(UserRegistry::from_json(broken).unwrap_err().to_string(), "line 1, column 2: missing field \"email\"");
+    let broken = "{\"users\": []}";
+    assert_eq!(UserRegistry::from_json(broken).unwrap_err().to_string(), "malformed JSON at line 1, column 1: expected an array but found '{'");
+
+    // Deeply nested input is an error rather than a stack overflow
+    let deep = "[".repeat(200_000);
+    let error = UserRegistry::from_json(&deep).unwrap_err().to_string();
+    assert_eq!(error, "malformed JSON at line 1, column 128: arrays and objects cannot be nested more than 128 deep");
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    assert!(json::parse(&nested(json::MAX_DEPTH)).is_ok());
+    assert!(json::parse(&nested(json::MAX_DEPTH + 1)).is_err());
+}
@@ -0,0 +1,51 @@
+// Width of text in a terminal
+//
+// format!("{:<10}", s) pads by chars, which only lines up while every char takes one column of the terminal.
+// CJK characters and most emoji take two columns and combining marks take none, so the columns are
+// measured in display width instead, following the East Asian Width ranges of Unicode.
+
+use crate::inspect::{graphemes, is_extender};
+
+// Chars shown two columns wide: Hangul, CJK, fullwidth forms and emoji
+fn is_wide(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{115f}' | '\u{2e80}'..='\u{303e}' | '\u{3041}'..='\u{33ff}' | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}' | '\u{a000}'..='\u{a4cf}' | '\u{ac00}'..='\u{d7a3}' | '\u{f900}'..='\u{faff}'
+        | '\u{fe30}'..='\u{fe4f}' | '\u{ff00}'..='\u{ff60}' | '\u{ffe0}'..='\u{ffe6}'
+        // Flags, pictographs, emoticons and supplemental symbols
+        | '\u{1f1e6}'..='\u{1f1ff}' | '\u{1f300}'..='\u{1f64f}' | '\u{1f680}'..='\u{1f6ff}' | '\u{1f900}'..='\u{1f9ff}'
+        // CJK extensions
+        | '\u{20000}'..='\u{3fffd}')
+}
+
+fn char_width(c: char) -> usize {
+    if c.is_control() || is_extender(c) {
+        0
+    } else if is_wide(c) {
+        2
+    } else {
+        1
+    }
+}
+
+// Number of terminal columns the text takes
+// A grapheme starting with a wide char like "👩‍💻" or "🇮🇳" is drawn once, however many chars it is made of
+pub fn display_width(s: &str) -> usize {
+    graphemes(s)
+        .iter()
+        .map(|g| match g.chars().next() {
+            Some(first) if is_wide(first) => 2,
+            _ => g.chars().map(char_width).sum(),
+        })
+        .sum()
+}
+
+// Pads the text with spaces up to the display width, on the left when 'right' is set
+pub fn pad(s: &str, width: usize, right: bool) -> String {
+    let padding = " ".repeat(width.saturating_sub(display_width(s)));
+    if right {
+        padding + s
+    } else {
+        format!("{}{}", s, padding)
+    }
+}
@@ -0,0 +1,324 @@
+// Compact binary encoding of enums::Message
+//
+// A message starts with a tag byte telling the variant, followed by its fields:
+//   0 Quit
+//   1 Move         x: i32, y: i32
+//   2 Write        length: u32, then that many bytes of UTF-8
+//   3 ChangeColor  r: i32, g: i32, b: i32
+// All integers are big-endian (network byte order)
+//
+// For streams every message is put into a frame, which is the message length as u32 followed by the message,
+// so a reader always knows where one message ends and the next begins
+
+use crate::enums::Message;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+const TAG_QUIT: u8 = 0;
+const TAG_MOVE: u8 = 1;
+const TAG_WRITE: u8 = 2;
+const TAG_CHANGE_COLOR: u8 = 3;
+
+// Frames larger than this are rejected before anything is allocated for them
+pub const MAX_FRAME_LEN: u32 = 1 << 20;
+
+#[derive(Debug, PartialEq)]
+pub enum EncodeError {
+    // Text of this many bytes doesn't fit the u32 length in front of it
+    TextTooLong(usize),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodeError::TextTooLong(len) => write!(f, "text of {} bytes is longer than {} bytes", len, u32::MAX),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    // Input ended while 'needed' more bytes were expected at 'offset'
+    Truncated { offset: usize, needed: usize },
+    UnknownTag { offset: usize, tag: u8 },
+    InvalidUtf8 { offset: usize },
+    // decode() expects exactly one message
+    TrailingBytes { offset: usize, count: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { offset, needed } => {
+                write!(f, "input ends at byte {} but {} more bytes were expected", offset, needed)
+            }
+            DecodeError::UnknownTag { offset, tag } => write!(f, "unknown tag {} at byte {}", tag, offset),
+            DecodeError::InvalidUtf8 { offset } => write!(f, "text at byte {} is not valid UTF-8", offset),
+            DecodeError::TrailingBytes { offset, count } => {
+                write!(f, "{} unexpected bytes after the message at byte {}", count, offset)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    Io(io::Error),
+    // Stream ended in the middle of a frame
+    Truncated,
+    TooLarge(usize),
+    Encode(EncodeError),
+    Decode(DecodeError),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FrameError::Io(e) => write!(f, "{}", e),
+            FrameError::Truncated => write!(f, "stream ended in the middle of a frame"),
+            FrameError::TooLarge(len) => write!(f, "frame of {} bytes is larger than {} bytes", len, MAX_FRAME_LEN),
+            FrameError::Encode(e) => write!(f, "{}", e),
+            FrameError::Decode(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for FrameError {
+    fn from(e: io::Error) -> FrameError {
+        FrameError::Io(e)
+    }
+}
+
+impl From<EncodeError> for FrameError {
+    fn from(e: EncodeError) -> FrameError {
+        FrameError::Encode(e)
+    }
+}
+
+impl From<DecodeError> for FrameError {
+    fn from(e: DecodeError) -> FrameError {
+        FrameError::Decode(e)
+    }
+}
+
+// Length in front of a text, which has to fit into u32
+fn text_length(len: usize) -> Result<[u8; 4], EncodeError> {
+    u32::try_from(len).map(u32::to_be_bytes).map_err(|_| EncodeError::TextTooLong(len))
+}
+
+// Appends the encoding of the message to 'out', which is left as it was on error
+pub fn encode_into(message: &Message, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+    match message {
+        Message::Quit => out.push(TAG_QUIT),
+        Message::Move { x, y } => {
+            out.push(TAG_MOVE);
+            out.extend_from_slice(&x.to_be_bytes());
+            out.extend_from_slice(&y.to_be_bytes());
+        }
+        Message::Write(text) => {
+            let len = text_length(text.len())?;
+            out.push(TAG_WRITE);
+            out.extend_from_slice(&len);
+            out.extend_from_slice(text.as_bytes());
+        }
+        Message::ChangeColor(r, g, b) => {
+            out.push(TAG_CHANGE_COLOR);
+            for channel in &[r, g, b] {
+                out.extend_from_slice(&channel.to_be_bytes());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn encode(message: &Message) -> Result<Vec<u8>, EncodeError> {
+    let mut out = Vec::new();
+    encode_into(message, &mut out)?;
+    Ok(out)
+}
+
+// Reads fields from the input while keeping track of the offset for error messages
+struct Decoder<'a> {
+    input: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let available = self.input.len() - self.offset;
+        if len > available {
+            return Err(DecodeError::Truncated { offset: self.input.len(), needed: len - available });
+        }
+        let bytes = &self.input[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, DecodeError> {
+        Ok(self.u32()? as i32)
+    }
+
+    fn message(&mut self) -> Result<Message, DecodeError> {
+        let offset = self.offset;
+        match self.u8()? {
+            TAG_QUIT => Ok(Message::Quit),
+            TAG_MOVE => Ok(Message::Move { x: self.i32()?, y: self.i32()? }),
+            TAG_WRITE => {
+                let len = self.u32()? as usize;
+                let text_offset = self.offset;
+                let bytes = self.take(len)?;
+                match std::str::from_utf8(bytes) {
+                    Ok(text) => Ok(Message::Write(text.to_string())),
+                    Err(_) => Err(DecodeError::InvalidUtf8 { offset: text_offset }),
+                }
+            }
+            TAG_CHANGE_COLOR => Ok(Message::ChangeColor(self.i32()?, self.i32()?, self.i32()?)),
+            tag => Err(DecodeError::UnknownTag { offset, tag }),
+        }
+    }
+}
+
+// Decodes the message at the start of the input and returns it along with the number of bytes it took
+pub fn decode_prefix(input: &[u8]) -> Result<(Message, usize), DecodeError> {
+    let mut decoder = Decoder { input, offset: 0 };
+    let message = decoder.message()?;
+    Ok((message, decoder.offset))
+}
+
+// Decodes input holding exactly one message
+pub fn decode(input: &[u8]) -> Result<Message, DecodeError> {
+    let (message, len) = decode_prefix(input)?;
+    if len < input.len() {
+        return Err(DecodeError::TrailingBytes { offset: len, count: input.len() - len });
+    }
+    Ok(message)
+}
+
+pub fn write_frame<W: Write>(writer: &mut W, message: &Message) -> Result<(), FrameError> {
+    let body = encode(message)?;
+    if body.len() > MAX_FRAME_LEN as usize {
+        return Err(FrameError::TooLarge(body.len()));
+    }
+    writer.write_all(&(body.len() as u32).to_be_bytes())?;
+    writer.write_all(&body)?;
+    Ok(())
+}
+
+// Fills 'buf' completely, returns false when the stream ends before the first byte
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<bool, FrameError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(FrameError::Truncated),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(FrameError::Io(e)),
+        }
+    }
+    Ok(true)
+}
+
+// Next message of the stream, None when the stream ends cleanly between two frames
+pub fn read_frame<R: Read>(reader: &mut R) -> Result<Option<Message>, FrameError> {
+    let mut len = [0u8; 4];
+    if !read_full(reader, &mut len)? {
+        return Ok(None);
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN as usize {
+        return Err(FrameError::TooLarge(len));
+    }
+
+    let mut body = vec![0u8; len];
+    if !read_full(reader, &mut body)? {
+        return Err(FrameError::Truncated);
+    }
+    Ok(Some(decode(&body)?))
+}
+
+fn random_message(rng: &mut impl Rng) -> Message {
+    match rng.gen_range(0, 4) {
+        0 => Message::Quit,
+        1 => Message::Move { x: rng.gen(), y: rng.gen() },
+        2 => {
+            let len = rng.gen_range(0, 20);
+            Message::Write((0..len).map(|_| rng.gen::<char>()).collect())
+        }
+        _ => Message::ChangeColor(rng.gen(), rng.gen(), rng.gen()),
+    }
+}
+
+pub fn run() {
+    let message = Message::Write(String::from("héllo"));
+    println!("{:?} is encoded as {:02x?}", message, encode(&message).unwrap());
+
+    // Random messages survive a round trip, and every shorter prefix of their encoding is reported as truncated
+    // The data is seeded so that a failure can be reproduced, the seed is part of every assert message
+    let seed = 41;
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..10_000 {
+        let message = random_message(&mut rng);
+        let bytes = encode(&message).unwrap();
+        assert_eq!(decode(&bytes).as_ref(), Ok(&message), "seed {} message {:?}", seed, message);
+        for len in 0..bytes.len() {
+            let (prefix, result) = (&bytes[..len], decode(&bytes[..len]));
+            assert!(matches!(result, Err(DecodeError::Truncated { .. })), "seed {} bytes {:02x?}: {:?}", seed, prefix, result);
+        }
+    }
+
+    // Random bytes either decode to a message which encodes back to the same bytes or give an error, but never panic
+    for _ in 0..10_000 {
+        let len = rng.gen_range(0, 16);
+        let bytes: Vec<u8> = (0..len).map(|_| rng.gen_range(0, 5)).collect();
+        if let Ok(message) = decode(&bytes) {
+            assert_eq!(encode(&message).as_ref(), Ok(&bytes), "seed {} bytes {:02x?}", seed, bytes);
+        }
+    }
+
+    // Lengths over u32::MAX are an error rather than being cut down to their lower 32 bits
+    assert_eq!(text_length(5), Ok([0, 0, 0, 5]));
+    assert_eq!(text_length(u32::MAX as usize), Ok([0xff; 4]));
+    if let Some(len) = (u32::MAX as usize).checked_add(1) {
+        assert_eq!(text_length(len), Err(EncodeError::TextTooLong(len)));
+        println!("{}", text_length(len).unwrap_err());
+    }
+
+    println!("{}", decode(&[7]).unwrap_err());
+    println!("{}", decode(&[TAG_MOVE, 0, 0, 0, 1, 0]).unwrap_err());
+    println!("{}", decode(&[TAG_WRITE, 0, 0, 0, 2, 0xc3, 0x28]).unwrap_err());
+    println!("{}", decode(&[TAG_QUIT, TAG_QUIT]).unwrap_err());
+
+    // Several frames sent over one stream are read back one at a time
+    let messages = vec![
+        Message::Move { x: -3, y: 4 },
+        Message::Write(String::from("framed")),
+        Message::ChangeColor(0, 128, 255),
+        Message::Quit,
+    ];
+    let mut stream = Vec::new();
+    for message in &messages {
+        write_frame(&mut stream, message).unwrap();
+    }
+    let mut reader = io::Cursor::new(&stream);
+    let mut received = Vec::new();
+    while let Some(message) = read_frame(&mut reader).unwrap() {
+        received.push(message);
+    }
+    assert_eq!(received, messages);
+    println!("{} messages in {} bytes", received.len(), stream.len());
+
+    println!("{}", read_frame(&mut io::Cursor::new(&stream[..6])).unwrap_err());
+}